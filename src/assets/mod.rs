@@ -2,6 +2,8 @@ use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 use bevy_kira_audio::AudioSource;
 
+use crate::{input::InputMap, level::LevelData};
+
 #[derive(AssetCollection, Resource)]
 pub struct GameAssets {
     #[asset(path = "sprites/bugLouse.png")]
@@ -10,6 +12,9 @@ pub struct GameAssets {
     #[asset(path = "sprites/bugDynamite.png")]
     pub exploding_louse_image: Handle<Image>,
 
+    #[asset(path = "sprites/heavy_louse.png")]
+    pub heavy_louse_image: Handle<Image>,
+
     #[asset(path = "sprites/dashedLine.png")]
     pub fire_line_image: Handle<Image>,
 
@@ -44,16 +49,7 @@ pub struct GameAssets {
     pub hair_top_damage_image: Handle<Image>,
 
     #[asset(path = "sprites/backGround.png")]
-    pub backGround: Handle<Image>,
-
-    #[asset(path = "sprites/hairBottom.png")]
-    pub hairBottom: Handle<Image>,
-
-    #[asset(path = "sprites/hairMiddle.png")]
-    pub hairMiddle: Handle<Image>,
-
-    #[asset(path = "sprites/hairTop.png")]
-    pub hairTop: Handle<Image>,
+    pub background: Handle<Image>,
 
     #[asset(path = "sprites/prompt.png")]
     pub prompt: Handle<Image>,
@@ -95,17 +91,6 @@ pub struct GameAssets {
     #[asset(paths("sprites/dandruff_big.png",), collection(typed))]
     pub dandruff_big_images: Vec<Handle<Image>>,
 
-    #[asset(
-        paths(
-            "sprites/dandruff_small1.png",
-            "sprites/dandruff_small2.png",
-            "sprites/dandruff_small3.png",
-            "sprites/dandruff_small4.png",
-        ),
-        collection(typed)
-    )]
-    pub dandruff_small_images: Vec<Handle<Image>>,
-
     #[asset(
         paths(
             "sprites/hair_flake1.png",
@@ -133,10 +118,6 @@ pub struct GameAssets {
     #[asset(path = "sounds/ScalpInvaders.mp3")]
     pub game_music: Handle<AudioSource>,
 
-    //put in gameover assetts later
-    #[asset(path = "sprites/game_over.png")]
-    pub game_over: Handle<Image>,
-
     #[asset(path = "sounds/hair_die.mp3")]
     pub hair_die: Handle<AudioSource>,
 
@@ -177,7 +158,7 @@ pub struct MenuAssets {
     #[asset(path = "sprites/button_go_selected.png")]
     pub button_go_selected: Handle<Image>,
 
-    #[asset(path = "sprites/menu_button_start.png")]
+    #[asset(path = "sprites/menu_button_start_plain.png")]
     pub menu_button_start: Handle<Image>,
 
     #[asset(path = "sprites/menu_button_start_selected.png")]
@@ -194,13 +175,24 @@ pub struct MenuAssets {
 
     #[asset(path = "sprites/screen_credits.png")]
     pub credits_screen: Handle<Image>,
-    
+
     #[asset(path = "sprites/dancing_louse_spritesheet.png")]
     pub dance_sheet: Handle<Image>,
-}
 
-#[derive(AssetCollection, Resource)]
-pub struct GameOverAssets {
-    // #[asset(path = "sprites/game_over.png")]
-    // pub game_over: Handle<Image>,
+    #[asset(path = "fonts/DejaVuSans-Bold.ttf")]
+    pub font: Handle<Font>,
+
+    #[asset(path = "config/default.bindings.ron")]
+    pub default_bindings: Handle<InputMap>,
+
+    // campaign levels, played in this order
+    #[asset(
+        paths(
+            "levels/level_1.level.ron",
+            "levels/level_2.level.ron",
+            "levels/level_3.level.ron",
+        ),
+        collection(typed)
+    )]
+    pub levels: Vec<Handle<LevelData>>,
 }
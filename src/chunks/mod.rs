@@ -1,4 +1,4 @@
-use crate::{assets, louse, states};
+use crate::{assets, collision, damage, louse, score, states};
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 use bevy_rapier2d::prelude::*;
@@ -7,6 +7,8 @@ use rand::{seq::SliceRandom, thread_rng, Rng};
 const CHUNK_SPIN: f32 = 0.5;
 const CHUNK_SCALE_MIN: f32 = 0.7;
 const CHUNK_SCALE_MAX: f32 = 1.2;
+// flying debris hurts hair, but much less than a louse
+const CHUNK_DAMAGE: f32 = 0.2;
 
 // Spawn a bunch of chunks from one position, exploding outwards
 pub fn spawn_chunk_explosion(
@@ -28,7 +30,7 @@ pub fn spawn_chunk_explosion(
             ),
             hitbox_size,
         );
-        i = i + 1;
+        i += 1;
     }
 }
 // Spawn a chunk in the game world
@@ -53,7 +55,7 @@ pub fn spawn_chunk(
             },
             ..Default::default()
         })
-        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS)
         .insert(RigidBody::Dynamic)
         .insert(Collider::cuboid(hitbox_size, hitbox_size))
         .insert(Velocity {
@@ -61,6 +63,10 @@ pub fn spawn_chunk(
             angvel: thread_rng().gen_range(-CHUNK_SPIN..=CHUNK_SPIN), // random spin
         })
         .insert(Restitution::new(0.0))
+        .insert(ReadMassProperties::default())
+        .insert(damage::DamagingComponent {
+            damage: CHUNK_DAMAGE,
+        })
         .insert(ChunkComponent)
         .insert(states::AppStateComponent(states::AppStates::Game));
 }
@@ -68,42 +74,28 @@ pub fn spawn_chunk(
 #[derive(Component)]
 pub struct ChunkComponent;
 
+// Lice that land on a chunk eat it and jump off again
 pub fn chunk_system(
     mut commands: Commands,
-    mut chunk_query: Query<Entity, With<ChunkComponent>>,
-    mut louse_query: Query<(Entity, &mut louse::BasicLouseComponent, &mut Velocity)>,
-    mut collision_events: EventReader<CollisionEvent>,
+    mut louse_query: Query<(&louse::BasicLouseComponent, &mut Velocity)>,
+    mut louse_ate_chunk_events: EventReader<collision::LouseAteChunkEvent>,
     game_assets: Res<assets::GameAssets>,
     audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
+    mut score: ResMut<score::ScoreResource>,
 ) {
-    let mut collision_events_vec = vec![];
-    for collision_event in collision_events.iter() {
-        collision_events_vec.push(collision_event);
-    }
+    for event in louse_ate_chunk_events.iter() {
+        let (louse_component, mut louse_velocity) = match louse_query.get_mut(event.louse) {
+            Ok(louse) => louse,
+            Err(_) => continue,
+        };
 
-    for chunk_entity in chunk_query.iter_mut() {
-        for (louse_entity, louse_component, mut louse_velocity) in louse_query.iter_mut() {
-            for event in collision_events_vec.iter() {
-                match event {
-                    CollisionEvent::Started(entity_1, entity_2, _) => {
-                        if (louse_entity == *entity_1 && chunk_entity == *entity_2)
-                            || (louse_entity == *entity_2 && chunk_entity == *entity_1)
-                        //&& louse_velocity.linvel.length() < 15.0
-                        {
-                            audio_channel.play(game_assets.munch.clone());
+        audio_channel.play(game_assets.munch.clone());
 
-                            commands.entity(chunk_entity).despawn();
-                            louse_velocity.linvel.x = thread_rng().gen_range(
-                                louse_component.jump_range_x.0..=louse_component.jump_range_x.1,
-                            );
-                            louse_velocity.linvel.y = thread_rng().gen_range(
-                                louse_component.jump_range_y.0..=louse_component.jump_range_y.1,
-                            );
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
+        commands.entity(event.chunk).despawn();
+        score.chunks_eaten += 1;
+        louse_velocity.linvel.x =
+            thread_rng().gen_range(louse_component.jump_range_x.0..=louse_component.jump_range_x.1);
+        louse_velocity.linvel.y =
+            thread_rng().gen_range(louse_component.jump_range_y.0..=louse_component.jump_range_y.1);
     }
 }
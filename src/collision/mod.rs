@@ -0,0 +1,60 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier2d::prelude::*;
+
+use crate::{chunks, hair, louse};
+
+// A louse started touching a hair segment
+pub struct LouseHitHairEvent {
+    pub louse: Entity,
+    pub hair: Entity,
+}
+
+// A louse that eats chunks touched one
+pub struct LouseAteChunkEvent {
+    pub louse: Entity,
+    pub chunk: Entity,
+}
+
+// A louse started touching anything
+pub struct LouseLandedEvent {
+    pub louse: Entity,
+}
+
+// Go over the physics collisions once and send the gameplay events they cause
+pub fn collision_dispatch_system(
+    mut collision_events: EventReader<CollisionEvent>,
+    louse_query: Query<Option<&louse::BasicLouseComponent>, With<louse::LouseComponent>>,
+    hair_query: Query<(), With<hair::HairComponent>>,
+    chunk_query: Query<(), With<chunks::ChunkComponent>>,
+    mut louse_hit_hair_events: EventWriter<LouseHitHairEvent>,
+    mut louse_ate_chunk_events: EventWriter<LouseAteChunkEvent>,
+    mut louse_landed_events: EventWriter<LouseLandedEvent>,
+) {
+    // several lice can touch the same chunk in one frame, but only one gets to eat it
+    let mut eaten_chunks = HashSet::new();
+
+    for event in collision_events.iter() {
+        let (entity_1, entity_2) = match event {
+            CollisionEvent::Started(entity_1, entity_2, _) => (*entity_1, *entity_2),
+            CollisionEvent::Stopped(..) => continue,
+        };
+
+        for (louse, other) in [(entity_1, entity_2), (entity_2, entity_1)] {
+            let eats_chunks = match louse_query.get(louse) {
+                Ok(basic_louse) => basic_louse.is_some(),
+                Err(_) => continue,
+            };
+
+            louse_landed_events.send(LouseLandedEvent { louse });
+
+            if hair_query.get(other).is_ok() {
+                louse_hit_hair_events.send(LouseHitHairEvent { louse, hair: other });
+            } else if eats_chunks && chunk_query.get(other).is_ok() && eaten_chunks.insert(other) {
+                louse_ate_chunk_events.send(LouseAteChunkEvent {
+                    louse,
+                    chunk: other,
+                });
+            }
+        }
+    }
+}
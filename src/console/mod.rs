@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    assets,
+    hair::{self, HairComponent},
+    level, louse,
+    states::{AppStateComponent, AppStates},
+};
+
+const CONSOLE_TOGGLE_KEY: KeyCode = KeyCode::Grave;
+const CONSOLE_POSITION: Vec2 = Vec2 {
+    x: -410.0,
+    y: 220.0,
+};
+const CONSOLE_FONT_SIZE: f32 = 14.0;
+
+const CONSOLE_HELP: &str =
+    "commands: win, lose, spawn <Basic|Exploding|Sticky|Splitter|Heavy> <x> <y>, hair <health>, reload, physics";
+
+// Text typed into the developer console and the output of the last command
+#[derive(Resource, Default)]
+pub struct ConsoleResource {
+    pub open: bool,
+    pub input: String,
+    pub output: String,
+}
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+// Open the console with the grave key, type a command and run it with enter
+#[allow(clippy::too_many_arguments)]
+pub fn console_input_system(
+    mut console: ResMut<ConsoleResource>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut received_characters: EventReader<ReceivedCharacter>,
+    mut app_state: ResMut<State<AppStates>>,
+    mut spawn_louse_events: EventWriter<louse::SpawnLouseEvent>,
+    mut hair_query: Query<&mut HairComponent>,
+    mut debug_render_context: ResMut<DebugRenderContext>,
+    current_level: Res<level::CurrentLevel>,
+    menu_assets: Res<assets::MenuAssets>,
+) {
+    if keyboard_input.just_pressed(CONSOLE_TOGGLE_KEY) {
+        console.open = !console.open;
+        console.input.clear();
+        received_characters.clear();
+        keyboard_input.clear();
+        return;
+    }
+
+    if !console.open {
+        received_characters.clear();
+        return;
+    }
+
+    for event in received_characters.iter() {
+        if !event.char.is_control() && event.char != '`' {
+            console.input.push(event.char);
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        let command = std::mem::take(&mut console.input);
+        console.output = match run_command(
+            &command,
+            &mut app_state,
+            &mut spawn_louse_events,
+            &mut hair_query,
+            &mut debug_render_context,
+            hair::cleared_level_state(current_level.0, menu_assets.levels.len()),
+        ) {
+            Ok(output) => output,
+            Err(err) => format!("error: {}", err),
+        };
+        info!("console: {} -> {}", command, console.output);
+    }
+
+    // keep typed keys from reaching the game, including ones held down like charge
+    let held_keys: Vec<KeyCode> = keyboard_input.get_pressed().copied().collect();
+    for key in held_keys {
+        keyboard_input.reset(key);
+    }
+    keyboard_input.clear();
+}
+
+fn run_command(
+    command: &str,
+    app_state: &mut State<AppStates>,
+    spawn_louse_events: &mut EventWriter<louse::SpawnLouseEvent>,
+    hair_query: &mut Query<&mut HairComponent>,
+    debug_render_context: &mut DebugRenderContext,
+    cleared_state: AppStates,
+) -> Result<String, String> {
+    let args: Vec<&str> = command.split_whitespace().collect();
+
+    match args.as_slice() {
+        ["win"] => {
+            app_state
+                .set(cleared_state)
+                .map_err(|err| err.to_string())?;
+            Ok("level cleared".to_string())
+        }
+        ["lose"] => {
+            app_state
+                .set(AppStates::GameOver)
+                .map_err(|err| err.to_string())?;
+            Ok("level lost".to_string())
+        }
+        ["spawn", louse_type, x, y] => {
+            let louse_type: louse::LouseType =
+                ron::from_str(louse_type).map_err(|err| err.to_string())?;
+            let position = Vec2::new(parse_arg(x)?, parse_arg(y)?);
+            spawn_louse_events.send(louse::SpawnLouseEvent {
+                position,
+                velocity: Vec2::ZERO,
+                louse_type: louse_type.clone(),
+                launched: false,
+            });
+            Ok(format!("spawned {:?} louse at {}", louse_type, position))
+        }
+        ["hair", health] => {
+            let health: f32 = parse_arg(health)?;
+            for mut hair in hair_query.iter_mut() {
+                hair.health = health;
+                hair.max_health = hair.max_health.max(health);
+            }
+            Ok(format!("hair health set to {}", health))
+        }
+        ["reload"] => {
+            app_state
+                .set(AppStates::LoadingGame)
+                .map_err(|err| err.to_string())?;
+            Ok("reloading level".to_string())
+        }
+        ["physics"] => {
+            debug_render_context.enabled = !debug_render_context.enabled;
+            Ok(format!(
+                "physics debug rendering {}",
+                if debug_render_context.enabled {
+                    "on"
+                } else {
+                    "off"
+                }
+            ))
+        }
+        [] | ["help"] => Ok(CONSOLE_HELP.to_string()),
+        _ => Err(format!("unknown command '{}', {}", command, CONSOLE_HELP)),
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(arg: &str) -> Result<T, String> {
+    arg.parse()
+        .map_err(|_| format!("could not parse '{}'", arg))
+}
+
+// Draw the console over the game while it is open
+pub fn console_ui_system(
+    mut commands: Commands,
+    console: Res<ConsoleResource>,
+    console_text_query: Query<Entity, With<ConsoleText>>,
+    menu_assets: Res<assets::MenuAssets>,
+) {
+    for entity in console_text_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if !console.open {
+        return;
+    }
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                format!("> {}_\n{}", console.input, console.output),
+                TextStyle {
+                    font: menu_assets.font.clone(),
+                    font_size: CONSOLE_FONT_SIZE,
+                    color: Color::YELLOW,
+                },
+            )
+            .with_alignment(TextAlignment::TOP_LEFT),
+            transform: Transform::from_translation(CONSOLE_POSITION.extend(100.0)),
+            ..Default::default()
+        })
+        .insert(ConsoleText)
+        .insert(AppStateComponent(AppStates::Game));
+}
+
+// Close the console when leaving the game
+pub fn close_console_system(mut console: ResMut<ConsoleResource>) {
+    console.open = false;
+    console.input.clear();
+}
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+// hits that change a body's speed by less than this, in pixels per second, do no damage,
+// so resting bodies don't grind hair down
+const MIN_DAMAGE_SPEED: f32 = 30.0;
+
+// Damage dealt to an entity, sent by any system that hurts things
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+// Body that damages what it hits, scaled by how much the hit slows it down.
+// Needs contact force events and ReadMassProperties to work
+#[derive(Component)]
+pub struct DamagingComponent {
+    pub damage: f32,
+}
+
+// Length of the substeps of the last physics step, the time rapier's contact forces and joint impulses are measured over
+#[derive(Resource)]
+pub struct PhysicsStepResource {
+    pub dt: f32,
+}
+
+impl Default for PhysicsStepResource {
+    fn default() -> Self {
+        PhysicsStepResource { dt: 1.0 / 60.0 }
+    }
+}
+
+// substep length rapier uses for the timestep mode, with the given frame time
+pub fn substep_dt(timestep_mode: TimestepMode, delta_seconds: f32) -> f32 {
+    match timestep_mode {
+        TimestepMode::Fixed { dt, substeps } | TimestepMode::Interpolated { dt, substeps, .. } => {
+            dt / substeps as f32
+        }
+        TimestepMode::Variable {
+            max_dt,
+            time_scale,
+            substeps,
+        } => (delta_seconds * time_scale).min(max_dt) / substeps as f32,
+    }
+}
+
+// Remember how long the physics step that just ran was, runs right after the step
+pub fn physics_step_system(
+    rapier_config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    mut physics_step: ResMut<PhysicsStepResource>,
+) {
+    if rapier_config.physics_pipeline_active {
+        physics_step.dt = substep_dt(rapier_config.timestep_mode, time.delta_seconds());
+    }
+}
+
+// Change of speed in pixels per second of a body of the given mass pushed by a contact force for one substep.
+// Rapier works in meters, so the force and mass are unscaled
+pub fn contact_speed_change(force_magnitude: f32, mass: f32, dt: f32, physics_scale: f32) -> f32 {
+    if mass <= 0.0 {
+        return 0.0;
+    }
+    force_magnitude * dt / mass * physics_scale
+}
+
+// damage of a hit that changed the attacker's speed by the given amount
+pub fn contact_damage(damage: f32, speed_change: f32) -> Option<f32> {
+    (speed_change > MIN_DAMAGE_SPEED).then_some(damage * speed_change)
+}
+
+// Turn the contact forces of damaging bodies into damage events for whatever they hit
+pub fn contact_damage_system(
+    mut contact_force_events: EventReader<ContactForceEvent>,
+    damaging_query: Query<(&DamagingComponent, &ReadMassProperties)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    rapier_context: Res<RapierContext>,
+    physics_step: Res<PhysicsStepResource>,
+) {
+    for event in contact_force_events.iter() {
+        for (attacker, target) in [
+            (event.collider1, event.collider2),
+            (event.collider2, event.collider1),
+        ] {
+            let (damaging, mass_properties) = match damaging_query.get(attacker) {
+                Ok(damaging) => damaging,
+                Err(_) => continue,
+            };
+
+            let speed_change = contact_speed_change(
+                event.total_force_magnitude,
+                mass_properties.0.mass,
+                physics_step.dt,
+                rapier_context.physics_scale(),
+            );
+            if let Some(amount) = contact_damage(damaging.damage, speed_change) {
+                damage_events.send(DamageEvent { target, amount });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // pixels per meter of the physics plugin
+    const PHYSICS_SCALE: f32 = 100.0;
+
+    #[test]
+    fn substeps_follow_the_timestep_mode() {
+        let variable = TimestepMode::Variable {
+            max_dt: 1.0 / 60.0,
+            time_scale: 1.0,
+            substeps: 1,
+        };
+        assert_eq!(substep_dt(variable, 1.0 / 144.0), 1.0 / 144.0);
+        assert_eq!(substep_dt(variable, 1.0 / 20.0), 1.0 / 60.0);
+
+        let fixed = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 2,
+        };
+        assert_eq!(substep_dt(fixed, 1.0 / 144.0), 1.0 / 120.0);
+    }
+
+    #[test]
+    fn full_pull_louse_hit_deals_damage() {
+        // a basic louse flung at full pull and stopped by the hit in one step
+        let definition = crate::louse::LouseType::Basic.definition();
+        let size = definition.hitbox * 2.0 / PHYSICS_SCALE;
+        let mass = size.x * size.y * definition.density;
+        let launch_speed = 150.0 * 7.0;
+        let dt = 1.0 / 144.0;
+        let force = mass * launch_speed / PHYSICS_SCALE / dt;
+
+        let speed_change = contact_speed_change(force, mass, dt, PHYSICS_SCALE);
+        assert!((speed_change - launch_speed).abs() < 1e-2);
+
+        let damage = contact_damage(definition.damage, speed_change).unwrap();
+        assert!((damage - definition.damage * launch_speed).abs() < 1e-2);
+    }
+
+    #[test]
+    fn resting_contacts_deal_no_damage() {
+        assert_eq!(contact_damage(1.0, 5.0), None);
+        assert_eq!(
+            contact_speed_change(10.0, 0.0, 1.0 / 60.0, PHYSICS_SCALE),
+            0.0
+        );
+    }
+}
@@ -1,11 +1,16 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 use bevy_kira_audio::{AudioChannel, AudioControl};
 use bevy_rapier2d::prelude::*;
 use rand::{seq::SliceRandom, thread_rng, Rng};
+use serde::Deserialize;
 
 use crate::{
     assets::{self, GameAssets},
-    chunks, louse,
+    chunks, damage, level, score,
     states::{self, AppStates},
 };
 
@@ -13,127 +18,404 @@ use crate::{
 const MID_SEG_LOW: i32 = 0;
 const MID_SEG_HIGH: i32 = 100;
 
-pub fn spawn_hair(commands: &mut Commands, game_assets: &assets::GameAssets, position: Vec2) {
-    let root_height = 23.0;
-    let seg_collider_height = 20.0;
-    let seg_position_multiplier = 60.0;
-    let joint_top = 30.0;
-    let joint_bottom = -30.0;
-    let top_extra_pos = 82.0;
-    let base_stiffness = 500.0;
-    let stiffness_decay = 1.4;
-    let base_radius = 14.0;
-    let radius_decay = 0.65;
-
-    let num_mid_segments = 10
-        - (thread_rng().gen_range(MID_SEG_LOW..=MID_SEG_HIGH) as f32)
+// half heights of the segment colliders
+const ROOT_HEIGHT: f32 = 23.0;
+const SEG_COLLIDER_HEIGHT: f32 = 20.0;
+const TOP_HEIGHT: f32 = 43.0;
+// joint anchors relative to the segment centers
+const ROOT_JOINT_TOP: f32 = 32.0;
+const SEG_JOINT_TOP: f32 = 30.0;
+const SEG_JOINT_BOTTOM: f32 = -30.0;
+
+// size regrown segments start at before growing to full size
+const REGROWTH_START_SCALE: f32 = 0.2;
+// seconds a regrown segment takes to reach full size
+const REGROWTH_GROW_TIME: f32 = 1.5;
+
+// force on a joint, in physics units, that snaps the hair above it off regardless of health
+const HAIR_BREAK_FORCE: f32 = 60.0;
+// damage multiplier of pieces of hair that broke off and are falling
+const DETACHED_HAIR_DAMAGE: f32 = 0.5;
+
+// angle each fork of a split end leans away from the hair
+const SPLIT_END_ANGLE: f32 = 0.45;
+// extra room between the forks and the segment below so the forks don't overlap
+const SPLIT_END_GAP: f32 = 12.0;
+
+// size of the hair images, stretched sideways to fit segments wider or thinner than normal hair
+const ROOT_SPRITE_SIZE: Vec2 = Vec2 { x: 50.0, y: 66.0 };
+const MID_SPRITE_SIZE: Vec2 = Vec2 { x: 19.0, y: 67.0 };
+const TOP_SPRITE_SIZE: Vec2 = Vec2 { x: 19.0, y: 109.0 };
+
+const CURLY_HAIR_COLOR: Color = Color::rgb(0.85, 0.65, 0.45);
+const THICK_HAIR_COLOR: Color = Color::rgb(0.6, 0.5, 0.45);
+const GRAY_HAIR_COLOR: Color = Color::rgb(0.85, 0.85, 0.9);
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum HairType {
+    #[default]
+    Normal,
+    Curly,
+    Thick,
+    SplitEnd,
+    Gray,
+}
+
+impl HairType {
+    // everything that sets the types of hair apart, new types only need an entry here
+    pub fn definition(&self) -> HairDefinition {
+        match self {
+            HairType::Normal => HairDefinition::default(),
+            // bends back and forth along its length and flops around more
+            HairType::Curly => HairDefinition {
+                tint: CURLY_HAIR_COLOR,
+                base_stiffness: 400.0,
+                stiffness_decay: 1.3,
+                radius_decay: 0.7,
+                mid_health: 420.0,
+                top_health: 380.0,
+                joint_limits: Some([-0.6, 0.6]),
+                curl: 0.35,
+                ..Default::default()
+            },
+            // stiff, tough and hard to snap
+            HairType::Thick => HairDefinition {
+                tint: THICK_HAIR_COLOR,
+                base_stiffness: 900.0,
+                stiffness_decay: 1.5,
+                base_radius: 18.0,
+                radius_decay: 0.75,
+                root_health: 800.0,
+                mid_health: 700.0,
+                top_health: 600.0,
+                joint_limits: Some([-0.3, 0.3]),
+                break_force: HAIR_BREAK_FORCE * 2.0,
+                ..Default::default()
+            },
+            // forks into two weak top segments
+            HairType::SplitEnd => HairDefinition {
+                top_health: 250.0,
+                split_end: true,
+                ..Default::default()
+            },
+            // old and brittle, breaks easily
+            HairType::Gray => HairDefinition {
+                tint: GRAY_HAIR_COLOR,
+                base_stiffness: 350.0,
+                stiffness_decay: 1.3,
+                root_health: 300.0,
+                mid_health: 260.0,
+                top_health: 220.0,
+                break_force: HAIR_BREAK_FORCE * 0.5,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+// Images of one part of a hair as it takes damage
+pub struct HairSegmentSprites {
+    pub orig: Handle<Image>,
+    pub broken: Handle<Image>,
+    pub damage: Handle<Image>,
+    pub size: Vec2,
+}
+
+// Every type of hair shares the images, only tinting and stretching them
+pub struct HairSprites {
+    pub root: HairSegmentSprites,
+    pub mid: HairSegmentSprites,
+    pub top: HairSegmentSprites,
+}
+
+impl HairSprites {
+    pub fn new(game_assets: &GameAssets) -> Self {
+        HairSprites {
+            root: HairSegmentSprites {
+                orig: game_assets.hair_root_image.clone(),
+                broken: game_assets.hair_root_broken_image.clone(),
+                damage: game_assets.hair_root_damage_image.clone(),
+                size: ROOT_SPRITE_SIZE,
+            },
+            mid: HairSegmentSprites {
+                orig: game_assets.hair_bottom_image.clone(),
+                broken: game_assets.hair_bottom_broken_image.clone(),
+                damage: game_assets.hair_bottom_damage_image.clone(),
+                size: MID_SPRITE_SIZE,
+            },
+            top: HairSegmentSprites {
+                orig: game_assets.hair_top_image.clone(),
+                broken: game_assets.hair_top_broken_image.clone(),
+                damage: game_assets.hair_top_damage_image.clone(),
+                size: TOP_SPRITE_SIZE,
+            },
+        }
+    }
+}
+
+// How a type of hair looks, bends and breaks
+pub struct HairDefinition {
+    pub tint: Color,
+    // motor stiffness of the lowest joint, multiplied by the decay for each joint above it
+    pub base_stiffness: f32,
+    pub stiffness_decay: f32,
+    // radius of the root, multiplied by the decay for each segment above it
+    pub base_radius: f32,
+    pub radius_decay: f32,
+    pub root_health: f32,
+    pub mid_health: f32,
+    pub top_health: f32,
+    // min and max angle of each joint
+    pub joint_limits: Option<[f32; 2]>,
+    // angle each joint bends to, alternating direction from one joint to the next
+    pub curl: f32,
+    pub break_force: f32,
+    // fork into two top segments
+    pub split_end: bool,
+}
+
+impl HairDefinition {
+    // radius of the segment at the given height, -1 being the root
+    pub fn segment_radius(&self, index: i32) -> f32 {
+        self.base_radius * f32::powf(self.radius_decay, index as f32 + 1.0)
+    }
+
+    // how much wider the segment at the given height is than on normal hair, to stretch its sprite by
+    pub fn sprite_width_scale(&self, index: i32) -> f32 {
+        self.segment_radius(index) / HairDefinition::default().segment_radius(index)
+    }
+}
+
+impl Default for HairDefinition {
+    fn default() -> Self {
+        HairDefinition {
+            tint: Color::WHITE,
+            base_stiffness: 500.0,
+            stiffness_decay: 1.4,
+            base_radius: 14.0,
+            radius_decay: 0.65,
+            root_health: 500.0,
+            mid_health: 450.0,
+            top_health: 400.0,
+            joint_limits: None,
+            curl: 0.0,
+            break_force: HAIR_BREAK_FORCE,
+            split_end: false,
+        }
+    }
+}
+
+// Spawn a hair at the given root position, picking a random number of mid segments if none is given
+pub fn spawn_hair(
+    commands: &mut Commands,
+    game_assets: &assets::GameAssets,
+    position: Vec2,
+    num_mid_segments: Option<i32>,
+    hair_type: HairType,
+) {
+    let definition = hair_type.definition();
+    let sprites = HairSprites::new(game_assets);
+
+    let num_mid_segments = num_mid_segments.unwrap_or_else(|| {
+        10 - (thread_rng().gen_range(MID_SEG_LOW..=MID_SEG_HIGH) as f32)
             .sqrt()
-            .floor() as i32;
+            .floor() as i32
+    });
     // create root segment
-    let root_entity = commands
-        .spawn(SpriteBundle {
-            texture: game_assets.hair_root_image.clone(),
-            transform: Transform::from_translation(position.extend(-1.0)),
-            ..Default::default()
-        })
-        .insert(RigidBody::Fixed)
-        .insert(Collider::capsule_y(root_height, base_radius))
-        .insert(Restitution::new(0.3))
-        .insert(states::AppStateComponent(states::AppStates::Game))
-        .insert(ActiveEvents::COLLISION_EVENTS)
-        .insert(HairComponent {
-            max_health: 500.0,
-            health: 500.0,
-            orig_image: game_assets.hair_root_image.clone(),
-            broken_image: game_assets.hair_root_broken_image.clone(),
-            damage_image: game_assets.hair_root_damage_image.clone(),
-            time_last_hit: f32::MIN,
-        })
-        .insert(RootComponent)
-        .id();
-
-    let bottom_joint_bottom = if num_mid_segments == 0 { -55.0 } else { -32.0 };
-    let bottom_joint = RevoluteJointBuilder::new()
-        .local_anchor1(Vec2::new(0.0, 32.0))
-        .local_anchor2(Vec2::new(0.0, bottom_joint_bottom))
-        .motor_position(0.0, base_stiffness, 10.0);
-
-    // use these to chain to the next midjoint
-    let mut prev_entity = root_entity;
-    let mut prev_joint = bottom_joint;
-    let mut i = 0;
-    while i < num_mid_segments {
-        let radius = base_radius * (f32::powf(radius_decay, i as f32 + 1.0));
-
-        let mid_entity = commands
-            .spawn(SpriteBundle {
-                texture: game_assets.hair_bottom_image.clone(),
-                transform: Transform::from_translation(Vec3::new(
-                    position.x,
-                    position.y + 64.0 + (i as f32) * seg_position_multiplier,
-                    -1.0,
-                )),
-                ..Default::default()
-            })
-            .insert(RigidBody::Dynamic)
-            .insert(Collider::capsule_y(seg_collider_height, radius))
-            .insert(Restitution::new(0.3))
-            .insert(states::AppStateComponent(states::AppStates::Game))
-            .insert(ImpulseJoint::new(prev_entity, prev_joint))
-            .insert(ActiveEvents::COLLISION_EVENTS)
-            .insert(HairComponent {
-                max_health: 450.0,
-                health: 450.0,
-                orig_image: game_assets.hair_bottom_image.clone(),
-                broken_image: game_assets.hair_bottom_broken_image.clone(),
-                damage_image: game_assets.hair_bottom_damage_image.clone(),
-                time_last_hit: f32::MIN,
-            })
-            .id();
-
-        let mid_joint_bottom = if i == num_mid_segments - 1 {
-            joint_bottom - 20.0
-        } else {
-            joint_bottom
-        };
-        let stiffness = base_stiffness * (f32::powf(stiffness_decay, i as f32 + 1.0));
-
-        let mid_joint = RevoluteJointBuilder::new()
-            .local_anchor1(Vec2::new(0.0, joint_top))
-            .local_anchor2(Vec2::new(0.0, mid_joint_bottom))
-            .motor_position(0.0, stiffness, 10.0);
-
-        i = i + 1;
-        prev_entity = mid_entity;
-        prev_joint = mid_joint;
-    }
-
-    let radius = base_radius * (f32::powf(radius_decay, i as f32 + 1.0));
-    let top_entity = commands
-        .spawn(SpriteBundle {
-            texture: game_assets.hair_top_image.clone(),
-            transform: Transform::from_translation(Vec3::new(
-                position.x,
-                position.y + top_extra_pos + (i as f32) * seg_position_multiplier,
-                -1.0,
-            )),
+    let root_transform = Transform::from_translation(position.extend(-1.0));
+    let root_entity = spawn_hair_segment(
+        commands,
+        &definition,
+        &sprites.root,
+        definition.sprite_width_scale(-1),
+        definition.root_health,
+        root_transform,
+    )
+    .insert(RigidBody::Fixed)
+    .insert(Collider::capsule_y(ROOT_HEIGHT, definition.base_radius))
+    .insert(RootComponent)
+    .insert(HairGrowthComponent {
+        hair_type,
+        num_mid_segments,
+        regrowth_progress: 0.0,
+    })
+    .id();
+
+    // chain each segment onto the one below it, ending with the top
+    let mut prev_segment = (root_entity, root_transform);
+    for index in 0..=num_mid_segments {
+        let segments: Vec<(Entity, Transform)> =
+            segment_forks(&definition, index, num_mid_segments)
+                .into_iter()
+                .map(|fork_angle| {
+                    grow_hair_segment(
+                        commands,
+                        &definition,
+                        &sprites,
+                        (index, fork_angle),
+                        index == num_mid_segments,
+                        prev_segment,
+                        1.0,
+                    )
+                })
+                .collect();
+        prev_segment = segments[0];
+    }
+}
+
+// Angles of the segments at the given height of the hair, the top of a split end forks in two
+fn segment_forks(definition: &HairDefinition, index: i32, num_mid_segments: i32) -> Vec<f32> {
+    if index == num_mid_segments && definition.split_end {
+        vec![-SPLIT_END_ANGLE, SPLIT_END_ANGLE]
+    } else {
+        vec![0.0]
+    }
+}
+
+// Spawn the segment at the given height of the hair, jointed onto the segment below it
+// and leaning by the fork angle. Anchors are scaled along with the segment
+fn grow_hair_segment(
+    commands: &mut Commands,
+    definition: &HairDefinition,
+    sprites: &HairSprites,
+    (index, fork_angle): (i32, f32),
+    is_top: bool,
+    (parent, parent_transform): (Entity, Transform),
+    scale: f32,
+) -> (Entity, Transform) {
+    let anchor_top = if index == 0 {
+        ROOT_JOINT_TOP
+    } else {
+        SEG_JOINT_TOP
+    };
+    let anchor_bottom = segment_anchor_bottom(index, is_top, fork_angle);
+
+    let radius = definition.segment_radius(index);
+    let (segment_sprites, health, collider) = if is_top {
+        (
+            &sprites.top,
+            definition.top_health,
+            Collider::capsule_y(TOP_HEIGHT, radius),
+        )
+    } else {
+        (
+            &sprites.mid,
+            definition.mid_health,
+            Collider::capsule_y(SEG_COLLIDER_HEIGHT, radius),
+        )
+    };
+
+    // start the segment where the joint holds it
+    let pivot =
+        parent_transform.translation + parent_transform.rotation * Vec3::new(0.0, anchor_top, 0.0);
+    let rotation = parent_transform.rotation * Quat::from_rotation_z(fork_angle);
+    let transform = Transform {
+        translation: pivot - rotation * Vec3::new(0.0, anchor_bottom * scale, 0.0),
+        rotation,
+        scale: Vec3::new(scale, scale, 1.0),
+    };
+    let joint = hair_joint(
+        definition,
+        index,
+        anchor_top,
+        anchor_bottom * scale,
+        fork_angle,
+    );
+
+    let entity = spawn_hair_segment(
+        commands,
+        definition,
+        segment_sprites,
+        definition.sprite_width_scale(index),
+        health,
+        transform,
+    )
+    .insert(RigidBody::Dynamic)
+    // lets explosions push the hair around
+    .insert(Velocity::zero())
+    .insert(collider)
+    .insert(ImpulseJoint::new(parent, joint))
+    .insert(HairSegmentComponent { fork_angle })
+    .id();
+    (entity, transform)
+}
+
+// Joint anchor at the bottom of a full size segment, relative to its center
+fn segment_anchor_bottom(index: i32, is_top: bool, fork_angle: f32) -> f32 {
+    let anchor_bottom = match (index, is_top) {
+        (0, true) => -55.0,
+        (_, true) => SEG_JOINT_BOTTOM - 20.0,
+        (0, false) => -32.0,
+        _ => SEG_JOINT_BOTTOM,
+    };
+    // extra room so the forks of a split end don't overlap
+    if fork_angle != 0.0 {
+        anchor_bottom - SPLIT_END_GAP
+    } else {
+        anchor_bottom
+    }
+}
+
+// Spawn the parts every segment of a hair shares
+fn spawn_hair_segment<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    definition: &HairDefinition,
+    sprites: &HairSegmentSprites,
+    width_scale: f32,
+    health: f32,
+    transform: Transform,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut segment = commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: definition.tint,
+            // match the width of the collider
+            custom_size: Some(sprites.size * Vec2::new(width_scale, 1.0)),
             ..Default::default()
-        })
-        .insert(RigidBody::Dynamic)
-        .insert(Collider::capsule_y(43.0, radius))
+        },
+        texture: sprites.orig.clone(),
+        transform,
+        ..Default::default()
+    });
+    segment
         .insert(Restitution::new(0.3))
         .insert(states::AppStateComponent(states::AppStates::Game))
-        .insert(ImpulseJoint::new(prev_entity, prev_joint))
         .insert(ActiveEvents::COLLISION_EVENTS)
         .insert(HairComponent {
-            max_health: 400.0,
-            health: 400.0,
-            orig_image: game_assets.hair_top_image.clone(),
-            broken_image: game_assets.hair_top_broken_image.clone(),
-            damage_image: game_assets.hair_top_damage_image.clone(),
+            max_health: health,
+            health,
+            orig_image: sprites.orig.clone(),
+            broken_image: sprites.broken.clone(),
+            damage_image: sprites.damage.clone(),
             time_last_hit: f32::MIN,
-        })
-        .id();
+            break_force: definition.break_force,
+        });
+    segment
+}
+
+// Joint holding up the segment at the given height of the hair, bent by the curl and the extra angle
+fn hair_joint(
+    definition: &HairDefinition,
+    index: i32,
+    anchor_top: f32,
+    anchor_bottom: f32,
+    angle: f32,
+) -> RevoluteJointBuilder {
+    let stiffness = definition.base_stiffness * f32::powf(definition.stiffness_decay, index as f32);
+    let curl = if index % 2 == 0 {
+        definition.curl
+    } else {
+        -definition.curl
+    };
+
+    let joint = RevoluteJointBuilder::new()
+        .local_anchor1(Vec2::new(0.0, anchor_top))
+        .local_anchor2(Vec2::new(0.0, anchor_bottom))
+        .motor_position(curl + angle, stiffness, 10.0);
+
+    match definition.joint_limits {
+        Some(limits) => joint.limits(limits),
+        None => joint,
+    }
 }
 
 #[derive(Component)]
@@ -144,53 +426,74 @@ pub struct HairComponent {
     pub broken_image: Handle<Image>,
     pub damage_image: Handle<Image>,
     pub time_last_hit: f32,
+    // force on the joint to the segment below that snaps it
+    pub break_force: f32,
 }
 
 #[derive(Component)]
 pub struct RootComponent;
 
+// Which fork of the hair a segment is, so regrowth knows which one is missing
+#[derive(Component)]
+pub struct HairSegmentComponent {
+    pub fork_angle: f32,
+}
+
+// What a root needs to regrow the segments it loses
+#[derive(Component)]
+pub struct HairGrowthComponent {
+    pub hair_type: HairType,
+    pub num_mid_segments: i32,
+    // a new segment grows when this reaches 1
+    pub regrowth_progress: f32,
+}
+
+// Segment that was regrown and is still growing to full size
+#[derive(Component)]
+pub struct GrowingHairComponent {
+    pub grow_timer: Timer,
+    // joint anchor below the segment at full size
+    pub anchor_bottom: f32,
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn hair_system(
     mut commands: Commands,
-    mut hair_query: Query<(Entity, &mut HairComponent, &mut Handle<Image>, &Transform)>,
-    louse_query: Query<(Entity, &louse::LouseComponent, &Velocity)>,
-    mut collision_events: EventReader<CollisionEvent>,
+    mut hair_query: Query<(
+        Entity,
+        &mut HairComponent,
+        &mut Handle<Image>,
+        &Transform,
+        Option<&RapierImpulseJointHandle>,
+    )>,
+    chain_query: Query<(Entity, &ImpulseJoint), With<HairComponent>>,
+    rapier_context: Res<RapierContext>,
+    physics_step: Res<damage::PhysicsStepResource>,
+    mut damage_events: EventReader<damage::DamageEvent>,
     game_assets: Res<assets::GameAssets>,
     audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
     time: Res<Time>,
+    mut score: ResMut<score::ScoreResource>,
 ) {
-    let mut collision_events_vec = vec![];
-    for collision_event in collision_events.iter() {
-        collision_events_vec.push(collision_event);
-    }
-
-    for (hair_entity, mut hair_component, mut image, transform) in hair_query.iter_mut() {
-        for (louse_entity, louse_component, louse_velocity) in louse_query.iter() {
-            for event in collision_events_vec.iter() {
-                match event {
-                    CollisionEvent::Stopped(entity_1, entity_2, _) => {
-                        if (louse_entity == *entity_1 && hair_entity == *entity_2)
-                            || (louse_entity == *entity_2 && hair_entity == *entity_1)
-                                && louse_velocity.linvel.length() > 5.0
-                        {
-                            hair_component.health -=
-                                louse_component.damage * louse_velocity.linvel.length();
-
-                            hair_component.time_last_hit = time.elapsed_seconds();
-
-                            audio_channel.play(
-                                game_assets
-                                    .crunch_sounds
-                                    .choose(&mut rand::thread_rng())
-                                    .unwrap()
-                                    .clone(),
-                            );
-                        }
-                    }
-                    _ => {}
-                }
-            }
+    for event in damage_events.iter() {
+        if let Ok((_, mut hair_component, _, _, _)) = hair_query.get_mut(event.target) {
+            hair_component.health -= event.amount;
+            hair_component.time_last_hit = time.elapsed_seconds();
+
+            audio_channel.play(
+                game_assets
+                    .crunch_sounds
+                    .choose(&mut rand::thread_rng())
+                    .unwrap()
+                    .clone(),
+            );
         }
+    }
 
+    let mut destroyed = HashSet::default();
+    let mut snapped = vec![];
+
+    for (hair_entity, hair_component, mut image, transform, joint_handle) in hair_query.iter_mut() {
         let t = time.elapsed_seconds();
 
         if t - hair_component.time_last_hit < 0.2 {
@@ -201,8 +504,18 @@ pub fn hair_system(
             *image = hair_component.orig_image.clone();
         }
 
+        let joint_force = joint_handle
+            .and_then(|handle| rapier_context.impulse_joints.get(handle.0))
+            .map(|joint| Vec2::new(joint.impulses.x, joint.impulses.y).length() / physics_step.dt)
+            .unwrap_or(0.0);
+        if joint_force > hair_component.break_force {
+            snapped.push(hair_entity);
+        }
+
         if hair_component.health <= 0.0 {
             commands.entity(hair_entity).despawn();
+            destroyed.insert(hair_entity);
+            score.segments_destroyed += 1;
 
             chunks::spawn_chunk_explosion(
                 &mut commands,
@@ -215,13 +528,344 @@ pub fn hair_system(
             audio_channel.play(game_assets.hair_die.clone());
         }
     }
+
+    // hair above a destroyed segment or a snapped joint falls off
+    let severed: Vec<Entity> = chain_query
+        .iter()
+        .filter(|(_, joint)| destroyed.contains(&joint.parent))
+        .map(|(entity, _)| entity)
+        .chain(snapped)
+        .filter(|entity| !destroyed.contains(entity))
+        .collect();
+
+    for segment in severed {
+        detach_hair(&mut commands, &chain_query, &destroyed, segment);
+    }
+}
+
+// Cut a segment loose from the one below it, turning it and everything above it into a falling piece that damages what it hits
+fn detach_hair(
+    commands: &mut Commands,
+    chain_query: &Query<(Entity, &ImpulseJoint), With<HairComponent>>,
+    destroyed: &HashSet<Entity>,
+    segment: Entity,
+) {
+    commands.entity(segment).remove::<ImpulseJoint>();
+
+    // split ends have more than one segment above them
+    let mut piece = vec![segment];
+    while let Some(entity) = piece.pop() {
+        if destroyed.contains(&entity) {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .insert(ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS)
+            .insert(ReadMassProperties::default())
+            .insert(damage::DamagingComponent {
+                damage: DETACHED_HAIR_DAMAGE,
+            });
+
+        piece.extend(
+            chain_query
+                .iter()
+                .filter(|(_, joint)| joint.parent == entity)
+                .map(|(entity, _)| entity),
+        );
+    }
+}
+
+// Regrow a segment missing from the part of each hair still attached to its root, one at a time
+pub fn hair_regrowth_system(
+    mut commands: Commands,
+    mut root_query: Query<(Entity, &Transform, &HairComponent, &mut HairGrowthComponent)>,
+    chain_query: Query<(
+        Entity,
+        &ImpulseJoint,
+        &Transform,
+        &HairComponent,
+        &HairSegmentComponent,
+    )>,
+    growing_query: Query<(), With<GrowingHairComponent>>,
+    level_resource: Res<level::LevelResource>,
+    game_assets: Res<assets::GameAssets>,
+    time: Res<Time>,
+) {
+    if level_resource.regrowth_rate <= 0.0 {
+        return;
+    }
+
+    // living segments jointed onto each segment
+    let mut children = HairChildren::default();
+    for (entity, joint, transform, hair, segment) in chain_query.iter() {
+        if hair.health > 0.0 {
+            children.entry(joint.parent).or_default().push((
+                entity,
+                *transform,
+                segment.fork_angle,
+            ));
+        }
+    }
+
+    for (root_entity, root_transform, root_hair, mut growth) in root_query.iter_mut() {
+        if root_hair.health <= 0.0 {
+            continue;
+        }
+
+        let definition = growth.hair_type.definition();
+
+        let missing = missing_segment(
+            &definition,
+            growth.num_mid_segments,
+            (root_entity, *root_transform),
+            &children,
+            |entity| growing_query.contains(entity),
+        );
+
+        let (parent, index, fork_angle) = match missing {
+            Some(missing) => missing,
+            None => {
+                growth.regrowth_progress = 0.0;
+                continue;
+            }
+        };
+
+        growth.regrowth_progress += level_resource.regrowth_rate * time.delta_seconds();
+        if growth.regrowth_progress < 1.0 {
+            continue;
+        }
+        growth.regrowth_progress = 0.0;
+
+        let sprites = HairSprites::new(&game_assets);
+        let (entity, _) = grow_hair_segment(
+            &mut commands,
+            &definition,
+            &sprites,
+            (index, fork_angle),
+            index == growth.num_mid_segments,
+            parent,
+            REGROWTH_START_SCALE,
+        );
+        commands.entity(entity).insert(GrowingHairComponent {
+            grow_timer: Timer::from_seconds(REGROWTH_GROW_TIME, TimerMode::Once),
+            anchor_bottom: segment_anchor_bottom(
+                index,
+                index == growth.num_mid_segments,
+                fork_angle,
+            ),
+        });
+    }
+}
+
+// Living segments jointed onto each segment, with their fork angles
+type HairChildren = HashMap<Entity, Vec<(Entity, Transform, f32)>>;
+
+// Walk up every fork from the root to the first gap, giving the segment to grow onto,
+// the height of the missing segment and its fork angle
+fn missing_segment(
+    definition: &HairDefinition,
+    num_mid_segments: i32,
+    root: (Entity, Transform),
+    children: &HairChildren,
+    is_growing: impl Fn(Entity) -> bool,
+) -> Option<((Entity, Transform), i32, f32)> {
+    let mut stack = vec![(root.0, root.1, -1)];
+    while let Some((entity, transform, index)) = stack.pop() {
+        if index == num_mid_segments {
+            continue;
+        }
+        let segments = children.get(&entity).map(Vec::as_slice).unwrap_or(&[]);
+        for fork_angle in segment_forks(definition, index + 1, num_mid_segments) {
+            match segments
+                .iter()
+                .find(|(_, _, segment_fork)| *segment_fork == fork_angle)
+            {
+                Some(&(segment, segment_transform, _)) => {
+                    stack.push((segment, segment_transform, index + 1));
+                }
+                // nothing grows on a segment that hasn't reached full size yet
+                None if !is_growing(entity) => {
+                    return Some(((entity, transform), index + 1, fork_angle));
+                }
+                None => {}
+            }
+        }
+    }
+    None
+}
+
+// Scale regrown segments and the joints holding them up to full size
+pub fn hair_growing_system(
+    mut commands: Commands,
+    mut growing_query: Query<(
+        Entity,
+        &mut GrowingHairComponent,
+        &mut Transform,
+        Option<&mut ImpulseJoint>,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, mut growing, mut transform, joint) in growing_query.iter_mut() {
+        growing.grow_timer.tick(time.delta());
+
+        let scale =
+            REGROWTH_START_SCALE + (1.0 - REGROWTH_START_SCALE) * growing.grow_timer.percent();
+        transform.scale = Vec3::new(scale, scale, 1.0);
+        // segments that broke off keep growing without a joint
+        if let Some(mut joint) = joint {
+            joint
+                .data
+                .set_local_anchor2(Vec2::new(0.0, growing.anchor_bottom * scale));
+        }
+
+        if growing.grow_timer.finished() {
+            commands.entity(entity).remove::<GrowingHairComponent>();
+        }
+    }
 }
 
 pub fn check_roots_system(
     root_query: Query<&RootComponent>,
     mut app_state: ResMut<State<AppStates>>,
+    current_level: Res<level::CurrentLevel>,
+    menu_assets: Res<assets::MenuAssets>,
 ) {
     if root_query.is_empty() {
-        app_state.set(AppStates::Victory).unwrap();
+        app_state
+            .set(cleared_level_state(
+                current_level.0,
+                menu_assets.levels.len(),
+            ))
+            .unwrap();
+    }
+}
+
+// only show the victory screen after the last level of the campaign
+pub fn cleared_level_state(level_index: usize, level_count: usize) -> AppStates {
+    if level_index + 1 < level_count {
+        AppStates::LevelComplete
+    } else {
+        AppStates::Victory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // segments as (entity, parent, fork angle), on a root that is entity 0
+    fn hair_children(segments: &[(u32, u32, f32)]) -> HairChildren {
+        let mut children = HairChildren::default();
+        for &(entity, parent, fork_angle) in segments {
+            children.entry(Entity::from_raw(parent)).or_default().push((
+                Entity::from_raw(entity),
+                Transform::default(),
+                fork_angle,
+            ));
+        }
+        children
+    }
+
+    fn missing(
+        hair_type: HairType,
+        children: &HairChildren,
+        growing: Option<u32>,
+    ) -> Option<(u32, i32, f32)> {
+        missing_segment(
+            &hair_type.definition(),
+            2,
+            (Entity::from_raw(0), Transform::default()),
+            children,
+            |entity| Some(entity.index()) == growing,
+        )
+        .map(|((parent, _), index, fork_angle)| (parent.index(), index, fork_angle))
+    }
+
+    #[test]
+    fn normal_hair_keeps_its_sprite_size() {
+        let definition = HairType::Normal.definition();
+        for index in -1..5 {
+            assert_eq!(definition.sprite_width_scale(index), 1.0);
+        }
+    }
+
+    #[test]
+    fn thick_hair_is_wider_tougher_and_harder_to_snap() {
+        let normal = HairType::Normal.definition();
+        let thick = HairType::Thick.definition();
+
+        for index in -1..5 {
+            assert!(thick.segment_radius(index) > normal.segment_radius(index));
+            assert!(thick.sprite_width_scale(index) > 1.0);
+        }
+        assert!(thick.root_health > normal.root_health);
+        assert!(thick.break_force > normal.break_force);
+        assert!(thick.base_stiffness > normal.base_stiffness);
+    }
+
+    #[test]
+    fn gray_hair_snaps_easier_than_normal_hair() {
+        let normal = HairType::Normal.definition();
+        let gray = HairType::Gray.definition();
+
+        assert!(gray.break_force < normal.break_force);
+        assert!(gray.mid_health < normal.mid_health);
+    }
+
+    #[test]
+    fn only_split_ends_fork_and_only_at_the_top() {
+        let split_end = HairType::SplitEnd.definition();
+        assert_eq!(
+            segment_forks(&split_end, 3, 3),
+            vec![-SPLIT_END_ANGLE, SPLIT_END_ANGLE]
+        );
+        assert_eq!(segment_forks(&split_end, 2, 3), vec![0.0]);
+        assert_eq!(
+            segment_forks(&HairType::Curly.definition(), 3, 3),
+            vec![0.0]
+        );
+    }
+
+    #[test]
+    fn complete_hair_grows_nothing() {
+        let children = hair_children(&[(1, 0, 0.0), (2, 1, 0.0), (3, 2, 0.0)]);
+        assert_eq!(missing(HairType::Normal, &children, None), None);
+    }
+
+    #[test]
+    fn regrows_above_the_highest_attached_segment() {
+        let children = hair_children(&[(1, 0, 0.0)]);
+        assert_eq!(
+            missing(HairType::Normal, &children, None),
+            Some((1, 1, 0.0))
+        );
+        assert_eq!(
+            missing(HairType::Normal, &hair_children(&[]), None),
+            Some((0, 0, 0.0))
+        );
+    }
+
+    #[test]
+    fn regrows_a_lost_fork_of_a_split_end() {
+        let children = hair_children(&[(1, 0, 0.0), (2, 1, 0.0), (3, 2, -SPLIT_END_ANGLE)]);
+        assert_eq!(
+            missing(HairType::SplitEnd, &children, None),
+            Some((2, 2, SPLIT_END_ANGLE))
+        );
+
+        let children = hair_children(&[
+            (1, 0, 0.0),
+            (2, 1, 0.0),
+            (3, 2, -SPLIT_END_ANGLE),
+            (4, 2, SPLIT_END_ANGLE),
+        ]);
+        assert_eq!(missing(HairType::SplitEnd, &children, None), None);
+    }
+
+    #[test]
+    fn waits_for_growing_segments_to_reach_full_size() {
+        let children = hair_children(&[(1, 0, 0.0)]);
+        assert_eq!(missing(HairType::Normal, &children, Some(1)), None);
     }
 }
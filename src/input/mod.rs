@@ -0,0 +1,456 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{assets, save};
+
+// Things the player can do, each bound to any number of inputs
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InputAction {
+    Confirm,
+    Back,
+    Credits,
+    Settings,
+    Pause,
+    Restart,
+    Quit,
+    Fling,
+    Charge,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 13] = [
+        InputAction::Confirm,
+        InputAction::Back,
+        InputAction::Credits,
+        InputAction::Settings,
+        InputAction::Pause,
+        InputAction::Restart,
+        InputAction::Quit,
+        InputAction::Fling,
+        InputAction::Charge,
+        InputAction::Up,
+        InputAction::Down,
+        InputAction::Left,
+        InputAction::Right,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl InputBinding {
+    // bindings of the same kind replace each other when rebinding
+    pub fn same_device(&self, other: &InputBinding) -> bool {
+        matches!(
+            (self, other),
+            (InputBinding::Key(_), InputBinding::Key(_))
+                | (InputBinding::Mouse(_), InputBinding::Mouse(_))
+                | (InputBinding::Gamepad(_), InputBinding::Gamepad(_))
+        )
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            InputBinding::Key(key) => format!("{:?}", key),
+            InputBinding::Mouse(button) => format!("{:?} Mouse", button),
+            InputBinding::Gamepad(button) => format!("Pad {:?}", button),
+        }
+    }
+}
+
+// Bindings of every action, loaded from a `.bindings.ron` file
+#[derive(Resource, Serialize, Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "9e3f4b8a-6c2d-4f1e-8b7a-3d5c1e2f4a60"]
+pub struct InputMap {
+    pub bindings: BTreeMap<InputAction, Vec<InputBinding>>,
+}
+
+// the config file is the only list of defaults, built in so there's a fallback before it loads
+const DEFAULT_BINDINGS: &str = include_str!("../../assets/config/default.bindings.ron");
+
+impl Default for InputMap {
+    fn default() -> Self {
+        ron::from_str(DEFAULT_BINDINGS).expect("default bindings should parse")
+    }
+}
+
+impl InputMap {
+    pub fn get(&self, action: InputAction) -> &[InputBinding] {
+        self.bindings
+            .get(&action)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    // replace the binding of the same kind of device, keeping the others
+    pub fn rebind(&mut self, action: InputAction, binding: InputBinding) {
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|old_binding| !old_binding.same_device(&binding));
+        bindings.insert(0, binding);
+    }
+
+    // name of the first binding of the action, for prompts
+    pub fn describe(&self, action: InputAction) -> String {
+        self.get(action)
+            .first()
+            .map(|binding| binding.name())
+            .unwrap_or_else(|| "unbound".to_string())
+    }
+}
+
+#[derive(Default)]
+pub struct InputMapLoader;
+
+impl AssetLoader for InputMapLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let input_map = ron::de::from_bytes::<InputMap>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(input_map));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bindings.ron"]
+    }
+}
+
+// Use the saved bindings over the default bindings from the config file
+pub fn apply_input_map_system(
+    mut input_map: ResMut<InputMap>,
+    input_map_assets: Res<Assets<InputMap>>,
+    menu_assets: Res<assets::MenuAssets>,
+    save_data: Res<save::SaveData>,
+) {
+    if let Some(default_bindings) = input_map_assets.get(&menu_assets.default_bindings) {
+        *input_map = default_bindings.clone();
+    }
+
+    // actions added since the bindings were saved keep their defaults
+    if let Some(saved_map) = &save_data.settings.bindings {
+        for (action, bindings) in saved_map.bindings.iter() {
+            input_map.bindings.insert(*action, bindings.clone());
+        }
+    }
+}
+
+// Reads actions through the input map instead of raw keys and buttons
+#[derive(SystemParam)]
+pub struct ActionInput<'w, 's> {
+    input_map: Res<'w, InputMap>,
+    keyboard_input: Res<'w, Input<KeyCode>>,
+    mouse_button: Res<'w, Input<MouseButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> ActionInput<'w, 's> {
+    fn inputs(&self) -> Inputs<'_> {
+        Inputs {
+            input_map: &self.input_map,
+            keyboard_input: &self.keyboard_input,
+            mouse_button: &self.mouse_button,
+            gamepads: &self.gamepads,
+            gamepad_buttons: &self.gamepad_buttons,
+        }
+    }
+
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.inputs().pressed(action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.inputs().just_pressed(action)
+    }
+
+    pub fn just_released(&self, action: InputAction) -> bool {
+        self.inputs().just_released(action)
+    }
+}
+
+// Action input for systems that consume the inputs they read or change the bindings,
+// the others use the read only ActionInput so they can run in parallel
+#[derive(SystemParam)]
+pub struct ActionInputMut<'w, 's> {
+    input_map: ResMut<'w, InputMap>,
+    keyboard_input: ResMut<'w, Input<KeyCode>>,
+    mouse_button: ResMut<'w, Input<MouseButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: ResMut<'w, Input<GamepadButton>>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> ActionInputMut<'w, 's> {
+    fn inputs(&self) -> Inputs<'_> {
+        Inputs {
+            input_map: &self.input_map,
+            keyboard_input: &self.keyboard_input,
+            mouse_button: &self.mouse_button,
+            gamepads: &self.gamepads,
+            gamepad_buttons: &self.gamepad_buttons,
+        }
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.inputs().just_pressed(action)
+    }
+
+    pub fn just_released(&self, action: InputAction) -> bool {
+        self.inputs().just_released(action)
+    }
+
+    // first key, mouse button or gamepad button pressed this frame
+    pub fn any_just_pressed(&self) -> Option<InputBinding> {
+        self.inputs().any_just_pressed()
+    }
+
+    // clear the inputs of the action so the next state doesn't read them again
+    pub fn reset(&mut self, action: InputAction) {
+        for binding in self.input_map.get(action).to_vec() {
+            match binding {
+                InputBinding::Key(key) => self.keyboard_input.reset(key),
+                InputBinding::Mouse(button) => self.mouse_button.reset(button),
+                InputBinding::Gamepad(button_type) => {
+                    for gamepad in self.gamepads.iter() {
+                        self.gamepad_buttons
+                            .reset(GamepadButton::new(gamepad, button_type));
+                    }
+                }
+            }
+        }
+    }
+
+    // raw mouse buttons, for clicking on menu buttons under the cursor
+    pub fn mouse_button(&self) -> &Input<MouseButton> {
+        &self.mouse_button
+    }
+
+    pub fn input_map(&self) -> &InputMap {
+        &self.input_map
+    }
+
+    // bind the input to the action and clear it so it doesn't trigger the action right away
+    pub fn rebind(&mut self, action: InputAction, binding: InputBinding) {
+        self.input_map.rebind(action, binding);
+        self.reset(action);
+    }
+}
+
+// The input resources both kinds of action input read from
+struct Inputs<'a> {
+    input_map: &'a InputMap,
+    keyboard_input: &'a Input<KeyCode>,
+    mouse_button: &'a Input<MouseButton>,
+    gamepads: &'a Gamepads,
+    gamepad_buttons: &'a Input<GamepadButton>,
+}
+
+impl<'a> Inputs<'a> {
+    fn check(
+        &self,
+        action: InputAction,
+        key_check: impl Fn(&Input<KeyCode>, KeyCode) -> bool,
+        mouse_check: impl Fn(&Input<MouseButton>, MouseButton) -> bool,
+        gamepad_check: impl Fn(&Input<GamepadButton>, GamepadButton) -> bool,
+    ) -> bool {
+        self.input_map
+            .get(action)
+            .iter()
+            .any(|binding| match binding {
+                InputBinding::Key(key) => key_check(self.keyboard_input, *key),
+                InputBinding::Mouse(button) => mouse_check(self.mouse_button, *button),
+                InputBinding::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| {
+                    gamepad_check(
+                        self.gamepad_buttons,
+                        GamepadButton::new(gamepad, *button_type),
+                    )
+                }),
+            })
+    }
+
+    fn pressed(&self, action: InputAction) -> bool {
+        self.check(
+            action,
+            |input, key| input.pressed(key),
+            |input, button| input.pressed(button),
+            |input, button| input.pressed(button),
+        )
+    }
+
+    fn just_pressed(&self, action: InputAction) -> bool {
+        self.check(
+            action,
+            |input, key| input.just_pressed(key),
+            |input, button| input.just_pressed(button),
+            |input, button| input.just_pressed(button),
+        )
+    }
+
+    fn just_released(&self, action: InputAction) -> bool {
+        self.check(
+            action,
+            |input, key| input.just_released(key),
+            |input, button| input.just_released(button),
+            |input, button| input.just_released(button),
+        )
+    }
+
+    fn any_just_pressed(&self) -> Option<InputBinding> {
+        if let Some(key) = self.keyboard_input.get_just_pressed().next() {
+            return Some(InputBinding::Key(*key));
+        }
+        if let Some(button) = self.mouse_button.get_just_pressed().next() {
+            return Some(InputBinding::Mouse(*button));
+        }
+        self.gamepad_buttons
+            .get_just_pressed()
+            .next()
+            .map(|button| InputBinding::Gamepad(button.button_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::AppStates;
+
+    #[test]
+    fn default_bindings_cover_every_action() {
+        let input_map = InputMap::default();
+
+        for action in InputAction::ALL {
+            assert!(!input_map.get(action).is_empty(), "{:?} is unbound", action);
+        }
+    }
+
+    // actions read by the systems running in each state
+    const STATE_ACTIONS: [(AppStates, &[InputAction]); 10] = [
+        (
+            AppStates::MainMenu,
+            &[
+                InputAction::Confirm,
+                InputAction::Up,
+                InputAction::Down,
+                InputAction::Credits,
+                InputAction::Settings,
+                InputAction::Quit,
+            ],
+        ),
+        (
+            AppStates::Game,
+            &[
+                InputAction::Fling,
+                InputAction::Charge,
+                InputAction::Up,
+                InputAction::Down,
+                InputAction::Pause,
+            ],
+        ),
+        (
+            AppStates::PauseMenu,
+            &[
+                InputAction::Confirm,
+                InputAction::Pause,
+                InputAction::Restart,
+                InputAction::Back,
+            ],
+        ),
+        (
+            AppStates::GameOver,
+            &[InputAction::Confirm, InputAction::Quit],
+        ),
+        (
+            AppStates::LevelComplete,
+            &[InputAction::Confirm, InputAction::Quit],
+        ),
+        (
+            AppStates::Victory,
+            &[InputAction::Confirm, InputAction::Quit],
+        ),
+        (AppStates::Instructions, &[InputAction::Confirm]),
+        (AppStates::Credits, &[InputAction::Back]),
+        (
+            AppStates::LevelSelect,
+            &[
+                InputAction::Confirm,
+                InputAction::Back,
+                InputAction::Up,
+                InputAction::Down,
+            ],
+        ),
+        (
+            AppStates::Settings,
+            &[
+                InputAction::Confirm,
+                InputAction::Back,
+                InputAction::Up,
+                InputAction::Down,
+                InputAction::Left,
+                InputAction::Right,
+            ],
+        ),
+    ];
+
+    #[test]
+    fn actions_of_a_state_dont_share_bindings() {
+        let input_map = InputMap::default();
+
+        for (state, actions) in STATE_ACTIONS {
+            for (i, action) in actions.iter().enumerate() {
+                for other_action in &actions[i + 1..] {
+                    for binding in input_map.get(*action) {
+                        assert!(
+                            !input_map.get(*other_action).contains(binding),
+                            "{:?} is bound to both {:?} and {:?} in {:?}",
+                            binding,
+                            action,
+                            other_action,
+                            state
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn input_map_round_trips() {
+        let mut input_map = InputMap::default();
+        input_map.rebind(InputAction::Fling, InputBinding::Mouse(MouseButton::Right));
+        input_map.rebind(InputAction::Up, InputBinding::Key(KeyCode::W));
+
+        let contents = ron::to_string(&input_map).unwrap();
+        let parsed: InputMap = ron::from_str(&contents).unwrap();
+
+        assert_eq!(parsed.bindings, input_map.bindings);
+        assert_eq!(
+            parsed.get(InputAction::Up),
+            &[
+                InputBinding::Key(KeyCode::W),
+                InputBinding::Gamepad(GamepadButtonType::DPadUp)
+            ]
+        );
+    }
+}
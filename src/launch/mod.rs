@@ -1,92 +1,546 @@
-use crate::{assets, level::LevelResource, louse};
+use crate::{
+    assets,
+    input::{self, InputAction},
+    level::LevelResource,
+    louse,
+    states::{AppStateComponent, AppStates},
+};
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 
+// seconds of flight between dots of the trajectory preview
+const TRAJECTORY_DOT_INTERVAL: f32 = 0.05;
+const TRAJECTORY_DOT_SIZE: f32 = 4.0;
+
+const SLINGSHOT_POST_SIZE: Vec2 = Vec2 { x: 8.0, y: 30.0 };
+const SLINGSHOT_BAND_WIDTH: f32 = 3.0;
+const POWER_METER_SIZE: Vec2 = Vec2 { x: 60.0, y: 8.0 };
+
+// keyboard and gamepad aiming, the origin is measured back from the fire line
+const AIM_ORIGIN_OFFSET: Vec2 = Vec2 { x: 120.0, y: -60.0 };
+const AIM_TURN_SPEED: f32 = 1.5;
+const AIM_CHARGE_RATE: f32 = 0.8;
+const AIM_IDLE_POWER: f32 = 0.5;
+const AIM_STICK_DEADZONE: f32 = 0.3;
+
 #[derive(Resource, Default, Debug)]
 pub struct LaunchResource {
     pub initial_position: Option<Vec2>,
+    // end of the band while aiming, limited to the maximum pull distance
+    pub pull_position: Option<Vec2>,
     pub velocity_multiplier: f32,
+    pub max_pull_distance: f32,
+    pub mouse_aiming: bool,
+    // the finger pulling the band, other touches are ignored
+    pub touch_id: Option<u64>,
+    // keyboard and gamepad aiming
+    pub aim_angle: f32,
+    pub aim_power: f32,
+    pub aim_charging: bool,
+    // the louse fired most recently, the only one whose ability can be triggered
+    pub last_launched: Option<Entity>,
+    // a tap used to trigger an ability, so it doesn't also start a pull
+    pub ability_touch_id: Option<u64>,
+}
+
+impl LaunchResource {
+    // cursor position limited to the maximum pull distance from the press point
+    pub fn clamp_pull(&self, initial_position: Vec2, cursor_position: Vec2) -> Vec2 {
+        initial_position
+            + (cursor_position - initial_position).clamp_length_max(self.max_pull_distance)
+    }
+
+    pub fn launch_velocity(&self, initial_position: Vec2, release_position: Vec2) -> Vec2 {
+        (initial_position - release_position) * self.velocity_multiplier
+    }
+
+    // drop any aim in progress without launching
+    pub fn cancel(&mut self) {
+        self.initial_position = None;
+        self.pull_position = None;
+        self.mouse_aiming = false;
+        self.touch_id = None;
+        self.aim_charging = false;
+    }
 }
 
+// Launch the next louse in the queue from the release position of the band
+fn release_louse(
+    initial_position: Vec2,
+    release_position: Vec2,
+    fling_resource: &LaunchResource,
+    level_resource: &mut LevelResource,
+    spawn_louse_events: &mut EventWriter<louse::SpawnLouseEvent>,
+    audio_channel: &AudioChannel<crate::SoundEffectsAudioChannel>,
+    game_assets: &assets::GameAssets,
+) {
+    if release_position.x < level_resource.fire_line {
+        let velocity = fling_resource.launch_velocity(initial_position, release_position);
+        audio_channel.stop();
+
+        audio_channel.play(game_assets.slingshot_release_sound.clone());
+
+        let louse_type = level_resource.louse_queue.pop();
+
+        if let Some(louse_type) = louse_type {
+            spawn_louse_events.send(louse::SpawnLouseEvent {
+                position: release_position,
+                velocity,
+                louse_type,
+                launched: true,
+            });
+        } else {
+            // TODO: Lose the game
+        }
+    } else {
+        audio_channel.stop();
+        audio_channel.play(game_assets.rrnt.clone());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn fling_louse_system(
     windows: Res<Windows>,
     camera: Query<(&Camera, &GlobalTransform), With<crate::MainCamera>>,
-    mouse_button: Res<Input<MouseButton>>,
+    action_input: input::ActionInput,
     mut spawn_louse_events: EventWriter<louse::SpawnLouseEvent>,
     mut fling_resource: ResMut<LaunchResource>,
     mut level_resource: ResMut<LevelResource>,
     audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
     game_assets: Res<assets::GameAssets>,
 ) {
-    if mouse_button.just_pressed(MouseButton::Left) {
-        let curr_window = windows.get_primary().unwrap();
-        let (camera, camera_gl_transform) = camera.single();
+    // keyboard, gamepad or touch aim in progress
+    if fling_resource.aim_charging || fling_resource.touch_id.is_some() {
+        return;
+    }
 
-        let initial_position =
-            get_cursor_physics_position(camera, camera_gl_transform, curr_window);
+    let curr_window = windows.get_primary().unwrap();
+    let (camera, camera_gl_transform) = camera.single();
+    let cursor_position = get_cursor_physics_position(camera, camera_gl_transform, curr_window);
 
-        if let Some(initial_pos_val) = initial_position {
-            if initial_pos_val.x < crate::FIRE_LINE && !level_resource.louse_queue.is_empty() {
-                fling_resource.initial_position = initial_position;
+    if action_input.just_pressed(InputAction::Fling) {
+        fling_resource.cancel();
+
+        if let Some(initial_pos_val) = cursor_position {
+            if initial_pos_val.x < level_resource.fire_line
+                && !level_resource.louse_queue.is_empty()
+            {
+                fling_resource.initial_position = cursor_position;
+                fling_resource.pull_position = cursor_position;
+                fling_resource.mouse_aiming = true;
                 audio_channel.play(game_assets.slingshot_pull_sound.clone());
             } else {
                 audio_channel.play(game_assets.rrnt.clone());
             }
         }
-    } else if mouse_button.just_released(MouseButton::Left) {
-        if let Some(initial_position) = fling_resource.initial_position {
-            let curr_window = windows.get_primary().unwrap();
-            let (camera, camera_gl_transform) = camera.single();
-
-            let final_position =
-                get_cursor_physics_position(camera, camera_gl_transform, curr_window);
+    } else if let Some(initial_position) = fling_resource
+        .initial_position
+        // only pulls started with the mouse are moved and released here
+        .filter(|_| fling_resource.mouse_aiming)
+    {
+        let final_position = cursor_position
+            .map(|cursor_position| fling_resource.clamp_pull(initial_position, cursor_position));
 
+        if action_input.just_released(InputAction::Fling) {
             if let Some(final_pos_val) = final_position {
-                if final_pos_val.x < crate::FIRE_LINE {
-                    let velocity =
-                        (initial_position - final_pos_val) * fling_resource.velocity_multiplier;
-                    audio_channel.stop();
-
-                    audio_channel.play(game_assets.slingshot_release_sound.clone());
-
-                    let louse_type = level_resource.louse_queue.pop();
-
-                    if let Some(louse_type) = louse_type {
-                        spawn_louse_events.send(louse::SpawnLouseEvent {
-                            position: final_pos_val,
-                            velocity,
-                            louse_type,
-                        });
-                    } else {
-                        // TODO: Lose the game
-                    }
+                release_louse(
+                    initial_position,
+                    final_pos_val,
+                    &fling_resource,
+                    &mut level_resource,
+                    &mut spawn_louse_events,
+                    &audio_channel,
+                    &game_assets,
+                );
+            } else {
+                audio_channel.play(game_assets.rrnt.clone());
+            }
+            fling_resource.cancel();
+        } else if action_input.pressed(InputAction::Fling) && final_position.is_some() {
+            fling_resource.pull_position = final_position;
+        }
+    }
+}
+
+// Drag a finger to pull the slingshot and lift it to fling
+#[allow(clippy::too_many_arguments)]
+pub fn touch_fling_louse_system(
+    windows: Res<Windows>,
+    camera: Query<(&Camera, &GlobalTransform), With<crate::MainCamera>>,
+    touches: Res<Touches>,
+    mut spawn_louse_events: EventWriter<louse::SpawnLouseEvent>,
+    mut fling_resource: ResMut<LaunchResource>,
+    mut level_resource: ResMut<LevelResource>,
+    audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
+    game_assets: Res<assets::GameAssets>,
+) {
+    if fling_resource.mouse_aiming || fling_resource.aim_charging {
+        return;
+    }
+
+    let curr_window = windows.get_primary().unwrap();
+    let (camera, camera_gl_transform) = camera.single();
+
+    match (fling_resource.touch_id, fling_resource.initial_position) {
+        (Some(touch_id), Some(initial_position)) => {
+            if let Some(touch) = touches.get_released(touch_id) {
+                let touch_position = get_touch_physics_position(
+                    camera,
+                    camera_gl_transform,
+                    curr_window,
+                    touch.position(),
+                );
+                let final_pos_val = fling_resource.clamp_pull(initial_position, touch_position);
+
+                release_louse(
+                    initial_position,
+                    final_pos_val,
+                    &fling_resource,
+                    &mut level_resource,
+                    &mut spawn_louse_events,
+                    &audio_channel,
+                    &game_assets,
+                );
+                fling_resource.cancel();
+            } else if let Some(touch) = touches.get_pressed(touch_id) {
+                let touch_position = get_touch_physics_position(
+                    camera,
+                    camera_gl_transform,
+                    curr_window,
+                    touch.position(),
+                );
+                fling_resource.pull_position =
+                    Some(fling_resource.clamp_pull(initial_position, touch_position));
+            } else {
+                // the touch was cancelled by the system
+                audio_channel.stop();
+                fling_resource.cancel();
+            }
+        }
+        _ => {
+            // only the first finger down starts a pull
+            if let Some(touch) = touches
+                .iter_just_pressed()
+                .find(|touch| Some(touch.id()) != fling_resource.ability_touch_id)
+            {
+                fling_resource.cancel();
+
+                let initial_pos_val = get_touch_physics_position(
+                    camera,
+                    camera_gl_transform,
+                    curr_window,
+                    touch.position(),
+                );
+
+                if initial_pos_val.x < level_resource.fire_line
+                    && !level_resource.louse_queue.is_empty()
+                {
+                    fling_resource.initial_position = Some(initial_pos_val);
+                    fling_resource.pull_position = Some(initial_pos_val);
+                    fling_resource.touch_id = Some(touch.id());
+                    audio_channel.play(game_assets.slingshot_pull_sound.clone());
                 } else {
-                    audio_channel.stop();
                     audio_channel.play(game_assets.rrnt.clone());
                 }
-            } else {
+            }
+        }
+    }
+}
+
+// Aim with the up and down actions or a gamepad stick and hold charge to build up the power
+#[allow(clippy::too_many_arguments)]
+pub fn aim_louse_system(
+    action_input: input::ActionInput,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut spawn_louse_events: EventWriter<louse::SpawnLouseEvent>,
+    mut fling_resource: ResMut<LaunchResource>,
+    mut level_resource: ResMut<LevelResource>,
+    audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
+    game_assets: Res<assets::GameAssets>,
+    time: Res<Time>,
+) {
+    let mut aim_input = false;
+
+    if action_input.pressed(InputAction::Up) {
+        fling_resource.aim_angle += AIM_TURN_SPEED * time.delta_seconds();
+        aim_input = true;
+    }
+    if action_input.pressed(InputAction::Down) {
+        fling_resource.aim_angle -= AIM_TURN_SPEED * time.delta_seconds();
+        aim_input = true;
+    }
+
+    let charge_input = action_input.pressed(InputAction::Charge);
+
+    for gamepad in gamepads.iter() {
+        let stick = Vec2::new(
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or(0.0),
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or(0.0),
+        );
+
+        if stick.length() > AIM_STICK_DEADZONE {
+            fling_resource.aim_angle = stick.y.atan2(stick.x);
+            aim_input = true;
+        }
+    }
+
+    fling_resource.aim_angle = fling_resource
+        .aim_angle
+        .clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+
+    // a mouse or touch drag in progress takes priority
+    if fling_resource.mouse_aiming || fling_resource.touch_id.is_some() {
+        return;
+    }
+
+    let aim_origin = Vec2::new(
+        level_resource.fire_line - AIM_ORIGIN_OFFSET.x,
+        AIM_ORIGIN_OFFSET.y,
+    );
+
+    if charge_input && !fling_resource.aim_charging {
+        if level_resource.louse_queue.is_empty() {
+            if action_input.just_pressed(InputAction::Charge) {
                 audio_channel.play(game_assets.rrnt.clone());
             }
+            return;
+        }
+
+        fling_resource.aim_charging = true;
+        fling_resource.aim_power = 0.0;
+        audio_channel.play(game_assets.slingshot_pull_sound.clone());
+    } else if charge_input {
+        fling_resource.aim_power =
+            (fling_resource.aim_power + AIM_CHARGE_RATE * time.delta_seconds()).min(1.0);
+    } else if fling_resource.aim_charging {
+        if let Some(pull_position) = fling_resource.pull_position {
+            release_louse(
+                aim_origin,
+                pull_position,
+                &fling_resource,
+                &mut level_resource,
+                &mut spawn_louse_events,
+                &audio_channel,
+                &game_assets,
+            );
+        }
+        fling_resource.cancel();
+        fling_resource.aim_power = 0.0;
+        return;
+    } else if !aim_input {
+        return;
+    }
+
+    // show the aim at a fixed power until charging starts
+    let direction = Vec2::new(
+        fling_resource.aim_angle.cos(),
+        fling_resource.aim_angle.sin(),
+    );
+    let power = if fling_resource.aim_charging {
+        fling_resource.aim_power
+    } else {
+        AIM_IDLE_POWER
+    };
+
+    fling_resource.initial_position = Some(aim_origin);
+    fling_resource.pull_position =
+        Some(aim_origin - direction * power * fling_resource.max_pull_distance);
+}
+
+#[derive(Component)]
+pub struct TrajectoryDot;
+
+// Draw the predicted flight path of the louse while the slingshot is pulled
+pub fn trajectory_preview_system(
+    mut commands: Commands,
+    fling_resource: Res<LaunchResource>,
+    level_resource: Res<LevelResource>,
+    trajectory_dot_query: Query<Entity, With<TrajectoryDot>>,
+) {
+    for entity in trajectory_dot_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if let (Some(initial_position), Some(pull_position)) = (
+        fling_resource.initial_position,
+        fling_resource.pull_position,
+    ) {
+        if pull_position.x >= level_resource.fire_line {
+            return;
         }
-        fling_resource.initial_position = None;
+
+        let velocity = fling_resource.launch_velocity(initial_position, pull_position);
+        let gravity = Vec2::new(0.0, crate::GRAVITY);
+
+        let mut t = TRAJECTORY_DOT_INTERVAL;
+        while t <= level_resource.trajectory_preview_time {
+            let dot_position = pull_position + velocity * t + 0.5 * gravity * t * t;
+
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgba(1.0, 1.0, 1.0, 0.7),
+                        custom_size: Some(Vec2::splat(TRAJECTORY_DOT_SIZE)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(dot_position.extend(1.0)),
+                    ..Default::default()
+                })
+                .insert(AppStateComponent(AppStates::Game))
+                .insert(TrajectoryDot);
+
+            t += TRAJECTORY_DOT_INTERVAL;
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SlingshotComponent;
+
+// Draw the slingshot at the press point with its band stretched to the pull position
+pub fn slingshot_system(
+    mut commands: Commands,
+    fling_resource: Res<LaunchResource>,
+    slingshot_query: Query<Entity, With<SlingshotComponent>>,
+) {
+    for entity in slingshot_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if let (Some(initial_position), Some(pull_position)) = (
+        fling_resource.initial_position,
+        fling_resource.pull_position,
+    ) {
+        // slingshot post
+        spawn_slingshot_rect(
+            &mut commands,
+            initial_position - Vec2::new(0.0, SLINGSHOT_POST_SIZE.y / 2.0),
+            SLINGSHOT_POST_SIZE,
+            0.0,
+            Color::rgb(0.45, 0.3, 0.15),
+        );
+
+        // elastic band
+        let pull = pull_position - initial_position;
+        spawn_slingshot_rect(
+            &mut commands,
+            initial_position + pull / 2.0,
+            Vec2::new(pull.length(), SLINGSHOT_BAND_WIDTH),
+            pull.y.atan2(pull.x),
+            Color::rgb(0.8, 0.2, 0.2),
+        );
+
+        // power meter, filled by how far the band is pulled
+        let power = if fling_resource.max_pull_distance > 0.0 {
+            pull.length() / fling_resource.max_pull_distance
+        } else {
+            0.0
+        };
+        let meter_position =
+            initial_position - Vec2::new(0.0, SLINGSHOT_POST_SIZE.y + POWER_METER_SIZE.y * 2.0);
+        spawn_slingshot_rect(
+            &mut commands,
+            meter_position,
+            POWER_METER_SIZE,
+            0.0,
+            Color::rgba(0.0, 0.0, 0.0, 0.6),
+        );
+        spawn_slingshot_rect(
+            &mut commands,
+            meter_position - Vec2::new(POWER_METER_SIZE.x * (1.0 - power) / 2.0, 0.0),
+            Vec2::new(POWER_METER_SIZE.x * power, POWER_METER_SIZE.y),
+            0.0,
+            Color::rgb(power, 1.0 - power, 0.0),
+        );
     }
 }
 
+fn spawn_slingshot_rect(
+    commands: &mut Commands,
+    position: Vec2,
+    size: Vec2,
+    angle: f32,
+    color: Color,
+) {
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            transform: Transform {
+                translation: position.extend(2.0),
+                rotation: Quat::from_rotation_z(angle),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::Game))
+        .insert(SlingshotComponent);
+}
+
 pub fn get_cursor_physics_position(
     camera: &Camera,
     camera_gl_transform: &GlobalTransform,
     window: &Window,
 ) -> Option<Vec2> {
-    if let Some(screen_pos) = window.cursor_position() {
-        let window_size = Vec2::new(window.width() as f32, window.height() as f32);
-        let ndc = (screen_pos / window_size) * 2.0 - Vec2::ONE;
-        let ndc_to_world =
-            camera_gl_transform.compute_matrix() * camera.projection_matrix().inverse();
-        let world_pos = ndc_to_world.project_point3(ndc.extend(-1.0));
-        let world_pos: Vec2 = world_pos.truncate();
-
-        Some(world_pos)
-    } else {
-        None
-    }
+    window.cursor_position().map(|screen_pos| {
+        get_screen_physics_position(camera, camera_gl_transform, window, screen_pos)
+    })
+}
+
+// touch positions start at the top of the window while the cursor starts at the bottom
+pub fn get_touch_physics_position(
+    camera: &Camera,
+    camera_gl_transform: &GlobalTransform,
+    window: &Window,
+    touch_pos: Vec2,
+) -> Vec2 {
+    let screen_pos = Vec2::new(touch_pos.x, window.height() - touch_pos.y);
+    get_screen_physics_position(camera, camera_gl_transform, window, screen_pos)
+}
+
+// World position of the first touch lifted this frame
+pub fn get_tap_physics_position(
+    camera: &Camera,
+    camera_gl_transform: &GlobalTransform,
+    window: &Window,
+    touches: &Touches,
+) -> Option<Vec2> {
+    touches.iter_just_released().next().map(|touch| {
+        get_touch_physics_position(camera, camera_gl_transform, window, touch.position())
+    })
+}
+
+// screen positions start at the bottom left of the window rather than of the letterboxed viewport
+fn get_screen_physics_position(
+    camera: &Camera,
+    camera_gl_transform: &GlobalTransform,
+    window: &Window,
+    screen_pos: Vec2,
+) -> Vec2 {
+    let viewport_pos = match &camera.viewport {
+        Some(viewport) => {
+            let scale_factor = window.scale_factor() as f32;
+            let top_left = viewport.physical_position.as_vec2() / scale_factor;
+            let size = viewport.physical_size.as_vec2() / scale_factor;
+            Vec2::new(
+                screen_pos.x - top_left.x,
+                screen_pos.y - (window.height() - top_left.y - size.y),
+            )
+        }
+        None => screen_pos,
+    };
+
+    // only fails while the window has no size, e.g. when minimized
+    camera
+        .viewport_to_world(camera_gl_transform, viewport_pos)
+        .map(|ray| ray.origin.truncate())
+        .unwrap_or_default()
 }
@@ -1,15 +1,113 @@
-use bevy::prelude::*;
-use bevy_rapier2d::prelude::Velocity;
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
 
 use crate::{
+    hair::HairType,
     louse::{LouseComponent, LouseType},
     states::AppStates,
 };
 
-#[derive(Resource, Debug)]
+#[derive(Resource, Debug, Default)]
 pub struct LevelResource {
     pub louse_queue: Vec<LouseType>,
     pub lose_timer: Timer,
+    pub fire_line: f32,
+    // seconds of flight shown by the aiming preview
+    pub trajectory_preview_time: f32,
+    // segments regrown per second by each root that has lost some, 0 turns regrowth off
+    pub regrowth_rate: f32,
+}
+
+impl LevelResource {
+    // build the level resource from loaded level data
+    pub fn from_data(level_data: &LevelData) -> Self {
+        // the queue is popped from the back, level files list lice in firing order
+        let mut louse_queue = level_data.louse_queue.clone();
+        louse_queue.reverse();
+
+        LevelResource {
+            louse_queue,
+            lose_timer: Timer::from_seconds(level_data.lose_time, TimerMode::Once),
+            fire_line: level_data.fire_line,
+            trajectory_preview_time: level_data.trajectory_preview_time,
+            regrowth_rate: level_data.regrowth_rate,
+        }
+    }
+}
+
+// Index into the campaign levels of the level being played
+#[derive(Resource, Debug, Default)]
+pub struct CurrentLevel(pub usize);
+
+// Level description loaded from a `.level.ron` file
+#[derive(Deserialize, TypeUuid, Debug, Clone)]
+#[uuid = "5b0c7a4e-3f0d-4a4f-9a55-2f6f3d1e8c21"]
+pub struct LevelData {
+    pub louse_queue: Vec<LouseType>,
+    pub fire_line: f32,
+    pub lose_time: f32,
+    pub hairs: Vec<HairData>,
+    pub chunks: Vec<ChunkSpawnData>,
+    // points needed for two and three stars
+    #[serde(default = "default_star_thresholds")]
+    pub star_thresholds: [u32; 2],
+    // seconds of flight shown by the aiming preview, 0 hides it
+    #[serde(default = "default_trajectory_preview_time")]
+    pub trajectory_preview_time: f32,
+    // segments regrown per second by each root that has lost some, hair doesn't regrow by default
+    #[serde(default)]
+    pub regrowth_rate: f32,
+}
+
+fn default_star_thresholds() -> [u32; 2] {
+    [3000, 6000]
+}
+
+fn default_trajectory_preview_time() -> f32 {
+    1.0
+}
+
+// Position of a hair root, optionally its number of mid segments and what kind of hair it is
+#[derive(Deserialize, Debug, Clone)]
+pub struct HairData {
+    pub position: (f32, f32),
+    #[serde(default)]
+    pub segments: Option<i32>,
+    #[serde(default)]
+    pub hair_type: HairType,
+}
+
+// Dandruff chunks scattered randomly inside a box
+#[derive(Deserialize, Debug, Clone)]
+pub struct ChunkSpawnData {
+    pub count: i32,
+    pub x_range: (f32, f32),
+    pub y_range: (f32, f32),
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level_data = ron::de::from_bytes::<LevelData>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level_data));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
 }
 
 pub fn lose_system(
@@ -25,3 +123,72 @@ pub fn lose_system(
         }
     }
 }
+
+// Start the campaign over from the first level
+pub fn reset_current_level_system(mut current_level: ResMut<CurrentLevel>) {
+    current_level.0 = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVELS: [&str; 3] = [
+        include_str!("../../assets/levels/level_1.level.ron"),
+        include_str!("../../assets/levels/level_2.level.ron"),
+        include_str!("../../assets/levels/level_3.level.ron"),
+    ];
+
+    #[test]
+    fn campaign_levels_parse() {
+        for contents in LEVELS {
+            let level_data: LevelData = ron::from_str(contents).unwrap();
+            assert!(!level_data.louse_queue.is_empty());
+            assert!(!level_data.hairs.is_empty());
+        }
+    }
+
+    #[test]
+    fn optional_fields_use_defaults() {
+        let level_data: LevelData = ron::from_str(
+            "(louse_queue: [Basic], fire_line: 0.0, lose_time: 5.0, hairs: [(position: (1.0, 2.0))], chunks: [])",
+        )
+        .unwrap();
+
+        assert_eq!(level_data.star_thresholds, default_star_thresholds());
+        assert_eq!(
+            level_data.trajectory_preview_time,
+            default_trajectory_preview_time()
+        );
+        assert_eq!(level_data.regrowth_rate, 0.0);
+        assert_eq!(level_data.hairs[0].segments, None);
+        assert!(matches!(level_data.hairs[0].hair_type, HairType::Normal));
+    }
+
+    #[test]
+    fn level_resource_fires_lice_in_file_order() {
+        let level_data: LevelData = ron::from_str(
+            "(louse_queue: [Exploding, Basic, Sticky], fire_line: -100.0, lose_time: 5.0, hairs: [], chunks: [], regrowth_rate: 0.5)",
+        )
+        .unwrap();
+
+        let mut level_resource = LevelResource::from_data(&level_data);
+
+        assert_eq!(level_resource.fire_line, -100.0);
+        assert_eq!(level_resource.regrowth_rate, 0.5);
+        assert_eq!(level_resource.lose_timer.duration().as_secs_f32(), 5.0);
+        assert!(matches!(
+            level_resource.louse_queue.pop(),
+            Some(LouseType::Exploding)
+        ));
+        assert!(matches!(
+            level_resource.louse_queue.pop(),
+            Some(LouseType::Basic)
+        ));
+        assert!(matches!(
+            level_resource.louse_queue.pop(),
+            Some(LouseType::Sticky)
+        ));
+        assert!(level_resource.louse_queue.is_empty());
+    }
+}
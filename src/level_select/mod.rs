@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets,
+    input::{self, InputAction},
+    launch, level, save,
+    states::{AppStateComponent, AppStates},
+};
+
+const LEVEL_GRID_COLUMNS: usize = 5;
+const LEVEL_TILE_SIZE: Vec2 = Vec2 { x: 140.0, y: 100.0 };
+const LEVEL_TILE_SPACING: Vec2 = Vec2 { x: 160.0, y: 120.0 };
+const LEVEL_GRID_TOP_Y: f32 = 80.0;
+
+const LEVEL_TILE_COLOR: Color = Color::rgb(0.3, 0.18, 0.1);
+const LEVEL_TILE_SELECTED_COLOR: Color = Color::rgb(0.7, 0.2, 0.2);
+const LEVEL_TILE_LOCKED_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+// Selected level tile and the last cursor position used for hovering
+#[derive(Resource, Default)]
+pub struct LevelSelectResource {
+    pub selected: usize,
+    pub last_cursor_position: Option<Vec2>,
+}
+
+#[derive(Component)]
+pub struct LevelTileComponent {
+    pub level_index: usize,
+    pub unlocked: bool,
+}
+
+pub fn setup_level_select_system(
+    mut commands: Commands,
+    menu_assets: Res<assets::MenuAssets>,
+    save_data: Res<save::SaveData>,
+    mut level_select_resource: ResMut<LevelSelectResource>,
+) {
+    // start on the furthest level that can be played
+    *level_select_resource = LevelSelectResource {
+        selected: (save_data.unlocked_levels.max(1) - 1).min(menu_assets.levels.len() - 1),
+        ..Default::default()
+    };
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "Select a level",
+                TextStyle {
+                    font: menu_assets.font.clone(),
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_xyz(0.0, 180.0, 1.0),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::LevelSelect));
+
+    let rows = menu_assets.levels.len().div_ceil(LEVEL_GRID_COLUMNS);
+    for level_index in 0..menu_assets.levels.len() {
+        let column = level_index % LEVEL_GRID_COLUMNS;
+        let row = level_index / LEVEL_GRID_COLUMNS;
+        // center each row on the screen
+        let columns_in_row = if row + 1 < rows {
+            LEVEL_GRID_COLUMNS
+        } else {
+            menu_assets.levels.len() - row * LEVEL_GRID_COLUMNS
+        };
+        let position = Vec2::new(
+            (column as f32 - (columns_in_row - 1) as f32 / 2.0) * LEVEL_TILE_SPACING.x,
+            LEVEL_GRID_TOP_Y - row as f32 * LEVEL_TILE_SPACING.y,
+        );
+
+        let unlocked = save_data.is_unlocked(level_index);
+        let description = if !unlocked {
+            "locked".to_string()
+        } else if let Some(best) = save_data.best_results.get(&level_index) {
+            let stars: String = (0..3)
+                .map(|i| {
+                    if i < best.stars {
+                        '\u{2605}'
+                    } else {
+                        '\u{2606}'
+                    }
+                })
+                .collect();
+            format!(
+                "{}\n{} lice left\n{:.1}s",
+                stars, best.lice_remaining, best.time_taken
+            )
+        } else {
+            "not cleared".to_string()
+        };
+
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: LEVEL_TILE_COLOR,
+                    custom_size: Some(LEVEL_TILE_SIZE),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(1.0)),
+                ..Default::default()
+            })
+            .insert(LevelTileComponent {
+                level_index,
+                unlocked,
+            })
+            .insert(AppStateComponent(AppStates::LevelSelect));
+
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_sections([
+                    TextSection::new(
+                        format!("Level {}\n", level_index + 1),
+                        TextStyle {
+                            font: menu_assets.font.clone(),
+                            font_size: 22.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    TextSection::new(
+                        description,
+                        TextStyle {
+                            font: menu_assets.font.clone(),
+                            font_size: 14.0,
+                            color: if unlocked { Color::GOLD } else { Color::GRAY },
+                        },
+                    ),
+                ])
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(position.extend(2.0)),
+                ..Default::default()
+            })
+            .insert(AppStateComponent(AppStates::LevelSelect));
+    }
+}
+
+// Pick an unlocked level with the cursor, a tap or up and down and confirm, or go back to the menu
+#[allow(clippy::too_many_arguments)]
+pub fn level_select_system(
+    mut action_input: input::ActionInputMut,
+    mut level_select_resource: ResMut<LevelSelectResource>,
+    mut tile_query: Query<(&LevelTileComponent, &Transform, &mut Sprite)>,
+    mut current_level: ResMut<level::CurrentLevel>,
+    mut app_state: ResMut<State<AppStates>>,
+    windows: Res<Windows>,
+    camera: Query<(&Camera, &GlobalTransform), With<crate::MainCamera>>,
+    touches: Res<Touches>,
+) {
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_gl_transform) = camera.single();
+    let cursor_position = launch::get_cursor_physics_position(camera, camera_gl_transform, window);
+    let tap_position =
+        launch::get_tap_physics_position(camera, camera_gl_transform, window, &touches);
+    let level_count = tile_query.iter().count();
+
+    let tile_at = |position: Vec2| {
+        tile_query
+            .iter()
+            .find(|(_, transform, _)| {
+                bevy::math::Rect::from_center_size(
+                    transform.translation.truncate(),
+                    LEVEL_TILE_SIZE,
+                )
+                .contains(position)
+            })
+            .map(|(tile, _, _)| tile.level_index)
+    };
+
+    let mut picked_level = None;
+
+    // only hover when the cursor moves so it doesn't fight the keyboard
+    if cursor_position != level_select_resource.last_cursor_position {
+        level_select_resource.last_cursor_position = cursor_position;
+        if let Some(hovered) = cursor_position.and_then(tile_at) {
+            level_select_resource.selected = hovered;
+        }
+    }
+
+    if action_input.mouse_button().just_released(MouseButton::Left) {
+        picked_level = cursor_position.and_then(tile_at);
+    }
+
+    if let Some(tapped) = tap_position.and_then(tile_at) {
+        picked_level = Some(tapped);
+    }
+
+    if level_count > 0 {
+        if action_input.just_pressed(InputAction::Up) {
+            level_select_resource.selected =
+                (level_select_resource.selected + level_count - 1) % level_count;
+        } else if action_input.just_pressed(InputAction::Down) {
+            level_select_resource.selected = (level_select_resource.selected + 1) % level_count;
+        }
+    }
+
+    if action_input.just_released(InputAction::Confirm) {
+        picked_level = Some(level_select_resource.selected);
+        action_input.reset(InputAction::Confirm);
+    }
+
+    let mut picked_unlocked = false;
+    for (tile, _, mut sprite) in tile_query.iter_mut() {
+        sprite.color = if !tile.unlocked {
+            LEVEL_TILE_LOCKED_COLOR
+        } else if tile.level_index == level_select_resource.selected {
+            LEVEL_TILE_SELECTED_COLOR
+        } else {
+            LEVEL_TILE_COLOR
+        };
+
+        if picked_level == Some(tile.level_index) && tile.unlocked {
+            picked_unlocked = true;
+        }
+    }
+
+    if let Some(level_index) = picked_level.filter(|_| picked_unlocked) {
+        current_level.0 = level_index;
+        app_state.set(AppStates::LoadingGame).unwrap();
+    } else if action_input.just_released(InputAction::Back) {
+        app_state.set(AppStates::MainMenu).unwrap();
+        action_input.reset(InputAction::Back);
+    }
+}
@@ -1,24 +1,305 @@
 use crate::{
     assets::{self, GameAssets},
     chunks::spawn_chunk_explosion,
+    collision,
+    damage,
     //effects::spawn_effect,
+    hair,
+    input::{self, InputAction},
+    launch,
     states,
 };
-use bevy::prelude::*;
+use bevy::{ecs::system::EntityCommands, prelude::*};
 //use bevy_hanabi::EffectAsset;
 use bevy_kira_audio::prelude::*;
 use bevy_rapier2d::prelude::*;
 use rand::{seq::SliceRandom, thread_rng, Rng};
+use serde::Deserialize;
 
 const LOUSE_HITBOX: Vec2 = Vec2 { x: 10.0, y: 10.0 };
 const LOUSE_SPIN: f32 = 2.0;
 const LOUSE_DESPAWN_TIME: f32 = 3.0;
 const LOUSE_MIN_VEL: f32 = 8.0;
 
-#[derive(Clone, Debug)]
+const EXPLOSION_RADIUS: f32 = 120.0;
+// damage to hair at the center of the explosion
+const EXPLOSION_DAMAGE: f32 = 300.0;
+// speed given to bodies at the center of the explosion
+const EXPLOSION_PUSH: f32 = 400.0;
+
+// sticky lice reuse the basic sprite with a tint
+const STICKY_LOUSE_COLOR: Color = Color::rgb(0.5, 1.0, 0.4);
+const STICKY_LOUSE_GNAW_INTERVAL: f32 = 0.5;
+const STICKY_LOUSE_GNAW_DAMAGE: f32 = 40.0;
+const STICKY_LOUSE_LATCH_TIME: f32 = 6.0;
+// bug parts left behind when a sticky louse lets go
+const STICKY_LOUSE_DEATH_CHUNKS: i32 = 5;
+
+const HEAVY_LOUSE_DENSITY: f32 = 12.0;
+// impulse given to hair segments per unit of the heavy louse's speed
+const HEAVY_LOUSE_KNOCKBACK: f32 = 0.3;
+// damage to hair roots per unit of the heavy louse's speed
+const HEAVY_LOUSE_ROOT_DAMAGE: f32 = 0.6;
+
+// splitter lice reuse the basic sprite with a tint
+const SPLITTER_LOUSE_COLOR: Color = Color::rgb(1.0, 0.85, 0.3);
+// angle between the paths of the lice a splitter louse divides into
+const SPLIT_ANGLE: f32 = 0.25;
+const SPLIT_SPACING: f32 = 22.0;
+
+#[derive(Clone, Debug, Deserialize)]
 pub enum LouseType {
     Basic,
     Exploding,
+    Sticky,
+    Splitter,
+    Heavy,
+}
+
+impl LouseType {
+    // everything that sets the types of lice apart, new types only need an entry here
+    pub fn definition(&self) -> LouseDefinition {
+        match self {
+            LouseType::Basic => LouseDefinition {
+                chunk_jump: Some(BasicLouseComponent {
+                    jump_range_x: (-200.0, 200.0),
+                    jump_range_y: (200.0, 500.0),
+                }),
+                ..Default::default()
+            },
+            LouseType::Exploding => LouseDefinition {
+                sprite: |game_assets| game_assets.exploding_louse_image.clone(),
+                density: 2.0,
+                damage: 1.0,
+                despawn_time: 0.1,
+                on_death: DeathAbility::Explode {
+                    radius: EXPLOSION_RADIUS,
+                    damage: EXPLOSION_DAMAGE,
+                    push: EXPLOSION_PUSH,
+                },
+                ..Default::default()
+            },
+            LouseType::Sticky => LouseDefinition {
+                tint: STICKY_LOUSE_COLOR,
+                damage: 0.4,
+                on_contact: Some(ContactAbility::Latch {
+                    gnaw_interval: STICKY_LOUSE_GNAW_INTERVAL,
+                    gnaw_damage: STICKY_LOUSE_GNAW_DAMAGE,
+                    latch_time: STICKY_LOUSE_LATCH_TIME,
+                }),
+                on_death: DeathAbility::Splatter {
+                    chunks: STICKY_LOUSE_DEATH_CHUNKS,
+                    chunk_size: 0.5,
+                },
+                ..Default::default()
+            },
+            LouseType::Splitter => LouseDefinition {
+                tint: SPLITTER_LOUSE_COLOR,
+                on_activate: Some(LouseAbility::Split),
+                ..Default::default()
+            },
+            LouseType::Heavy => LouseDefinition {
+                sprite: |game_assets| game_assets.heavy_louse_image.clone(),
+                density: HEAVY_LOUSE_DENSITY,
+                // pushes hair over rather than chipping at it
+                damage: 0.2,
+                on_contact: Some(ContactAbility::Knockback {
+                    knockback: HEAVY_LOUSE_KNOCKBACK,
+                    root_damage: HEAVY_LOUSE_ROOT_DAMAGE,
+                }),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+// How a type of louse looks, flies and behaves
+pub struct LouseDefinition {
+    pub sprite: fn(&GameAssets) -> Handle<Image>,
+    pub tint: Color,
+    // half extents of the collider
+    pub hitbox: Vec2,
+    pub density: f32,
+    // damage to hair per unit of speed on impact
+    pub damage: f32,
+    // seconds the louse can rest before it dies
+    pub despawn_time: f32,
+    pub on_contact: Option<ContactAbility>,
+    pub on_death: DeathAbility,
+    pub on_activate: Option<LouseAbility>,
+    // jumping at nearby chunks of dandruff
+    pub chunk_jump: Option<BasicLouseComponent>,
+}
+
+impl Default for LouseDefinition {
+    fn default() -> Self {
+        LouseDefinition {
+            sprite: |game_assets| game_assets.basic_louse_image.clone(),
+            tint: Color::WHITE,
+            hitbox: LOUSE_HITBOX,
+            density: 1.0,
+            damage: 0.8,
+            despawn_time: LOUSE_DESPAWN_TIME,
+            on_contact: None,
+            on_death: DeathAbility::Squish,
+            on_activate: None,
+            chunk_jump: None,
+        }
+    }
+}
+
+// Behavior when the louse touches hair
+#[derive(Clone, Copy, Debug)]
+pub enum ContactAbility {
+    // hold on to the hair and damage it over time
+    Latch {
+        gnaw_interval: f32,
+        gnaw_damage: f32,
+        latch_time: f32,
+    },
+    // push the hair along the louse's path and hit roots harder
+    Knockback {
+        knockback: f32,
+        root_damage: f32,
+    },
+}
+
+impl ContactAbility {
+    fn insert_component(&self, louse: &mut EntityCommands, velocity: Vec2) {
+        match *self {
+            ContactAbility::Latch {
+                gnaw_interval,
+                gnaw_damage,
+                latch_time,
+            } => {
+                louse.insert(StickyLouseComponent {
+                    latched_hair: None,
+                    gnaw_damage,
+                    gnaw_timer: Timer::from_seconds(gnaw_interval, TimerMode::Repeating),
+                    latch_timer: Timer::from_seconds(latch_time, TimerMode::Once),
+                });
+            }
+            ContactAbility::Knockback {
+                knockback,
+                root_damage,
+            } => {
+                louse.insert(HeavyLouseComponent {
+                    knockback,
+                    root_damage,
+                    pre_contact_velocity: velocity,
+                });
+            }
+        }
+    }
+}
+
+// Behavior when the louse dies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeathAbility {
+    Squish,
+    // squish and scatter some bug parts
+    Splatter { chunks: i32, chunk_size: f32 },
+    // damage hair and push bodies away within the radius, less so further out
+    Explode { radius: f32, damage: f32, push: f32 },
+}
+
+impl DeathAbility {
+    pub fn trigger(
+        &self,
+        commands: &mut Commands,
+        game_assets: &GameAssets,
+        audio_channel: &AudioChannel<crate::SoundEffectsAudioChannel>,
+        explosion_events: &mut EventWriter<ExplosionEvent>,
+        position: Vec2,
+    ) {
+        match *self {
+            DeathAbility::Squish => {
+                audio_channel.play(game_assets.bug_squish.clone());
+            }
+            DeathAbility::Splatter { chunks, chunk_size } => {
+                spawn_chunk_explosion(
+                    commands,
+                    game_assets.bug_parts.clone(),
+                    position,
+                    chunks,
+                    chunk_size,
+                );
+                audio_channel.play(game_assets.bug_squish.clone());
+            }
+            DeathAbility::Explode {
+                radius,
+                damage,
+                push,
+            } => {
+                explosion_events.send(ExplosionEvent {
+                    position,
+                    radius,
+                    damage,
+                    push,
+                });
+                spawn_chunk_explosion(commands, game_assets.bug_parts.clone(), position, 15, 1.0);
+                spawn_chunk_explosion(commands, game_assets.bug_head.clone(), position, 1, 1.5);
+                audio_channel.play(game_assets.bug_explode.clone());
+            }
+        }
+    }
+}
+
+// Event data describing an explosion where a louse died
+pub struct ExplosionEvent {
+    pub position: Vec2,
+    pub radius: f32,
+    pub damage: f32,
+    pub push: f32,
+}
+
+// Damage hair and push bodies away from explosions, falling off towards the edge of the blast
+pub fn explosion_system(
+    rapier_context: Res<RapierContext>,
+    mut explosion_events: EventReader<ExplosionEvent>,
+    mut body_query: Query<(&Transform, Option<&RigidBody>, Option<&mut Velocity>)>,
+    hair_query: Query<(), With<hair::HairComponent>>,
+    mut damage_events: EventWriter<damage::DamageEvent>,
+) {
+    for event in explosion_events.iter() {
+        let mut hit_entities = vec![];
+        rapier_context.intersections_with_shape(
+            event.position,
+            0.0,
+            &Collider::ball(event.radius),
+            QueryFilter::default(),
+            |entity| {
+                hit_entities.push(entity);
+                true
+            },
+        );
+
+        for entity in hit_entities {
+            let (transform, rigid_body, velocity) = match body_query.get_mut(entity) {
+                Ok(body) => body,
+                Err(_) => continue,
+            };
+
+            let offset = transform.translation.truncate() - event.position;
+            let falloff = explosion_falloff(offset, event.radius);
+
+            if hair_query.get(entity).is_ok() {
+                damage_events.send(damage::DamageEvent {
+                    target: entity,
+                    amount: event.damage * falloff,
+                });
+            }
+
+            if let (Some(RigidBody::Dynamic), Some(mut velocity)) = (rigid_body, velocity) {
+                velocity.linvel += offset.normalize_or_zero() * event.push * falloff;
+            }
+        }
+    }
+}
+
+// Share of an explosion's strength felt at the given offset from its center
+pub fn explosion_falloff(offset: Vec2, radius: f32) -> f32 {
+    (1.0 - offset.length() / radius).clamp(0.0, 1.0)
 }
 
 // Event data describing spawning lice
@@ -26,6 +307,8 @@ pub struct SpawnLouseEvent {
     pub position: Vec2,
     pub velocity: Vec2,
     pub louse_type: LouseType,
+    // fired from the slingshot, only the last launched louse can use its ability
+    pub launched: bool,
 }
 
 // Checks for lice spawning events and spawns the lice
@@ -35,15 +318,19 @@ pub fn spawn_louse_system(
     mut spawn_louse_events: EventReader<SpawnLouseEvent>,
     audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
     game_assets: Res<assets::GameAssets>,
+    mut launch_resource: ResMut<launch::LaunchResource>,
 ) {
     for event in spawn_louse_events.iter() {
-        spawn_louse(
+        let louse = spawn_louse(
             &mut commands,
             &sprite_assets,
             event.position,
             event.velocity,
             event.louse_type.clone(),
         );
+        if event.launched {
+            launch_resource.last_launched = Some(louse);
+        }
         audio_channel.play(
             game_assets
                 .wahoo_sounds
@@ -61,79 +348,279 @@ pub fn spawn_louse(
     position: Vec2,
     velocity: Vec2,
     louse_type: LouseType,
-) {
-    match louse_type {
-        LouseType::Basic => {
-            commands
-                .spawn(SpriteBundle {
-                    texture: sprite_assets.basic_louse_image.clone(),
-                    transform: Transform::from_translation(position.extend(0.0)),
-                    ..Default::default()
-                })
-                .insert(RigidBody::Dynamic)
-                .insert(Collider::cuboid(LOUSE_HITBOX.x, LOUSE_HITBOX.y))
-                .insert(Velocity {
-                    linvel: velocity,
-                    angvel: thread_rng().gen_range(-LOUSE_SPIN..=LOUSE_SPIN), // random spin
-                })
-                .insert(Restitution::new(0.0))
-                .insert(ActiveEvents::COLLISION_EVENTS)
-                .insert(LouseComponent {
-                    damage: 0.8,
-                    despawn_timer: Timer::from_seconds(LOUSE_DESPAWN_TIME, TimerMode::Once),
-                    should_explode: false,
-                })
-                .insert(BasicLouseComponent {
-                    jump_range_x: (-200.0, 200.0),
-                    jump_range_y: (200.0, 500.0),
-                })
-                .insert(states::AppStateComponent(states::AppStates::Game));
-        }
-        LouseType::Exploding => {
-            commands
-                .spawn(SpriteBundle {
-                    texture: sprite_assets.exploding_louse_image.clone(),
-                    transform: Transform::from_translation(position.extend(0.0)),
-                    ..Default::default()
-                })
-                .insert(RigidBody::Dynamic)
-                .insert(Collider::cuboid(LOUSE_HITBOX.x, LOUSE_HITBOX.y))
-                .insert(Velocity {
-                    linvel: velocity,
-                    angvel: thread_rng().gen_range(-LOUSE_SPIN..=LOUSE_SPIN), // random spin
-                })
-                .insert(Restitution::new(0.0))
-                .insert(ColliderMassProperties::Density(2.0))
-                .insert(ActiveEvents::COLLISION_EVENTS)
-                .insert(LouseComponent {
-                    damage: 1.0,
-                    despawn_timer: Timer::from_seconds(0.1, TimerMode::Once),
-                    should_explode: true,
-                })
-                .insert(states::AppStateComponent(states::AppStates::Game));
-        }
-    };
+) -> Entity {
+    let definition = louse_type.definition();
+
+    let mut louse = commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: definition.tint,
+            ..Default::default()
+        },
+        texture: (definition.sprite)(sprite_assets),
+        transform: Transform::from_translation(position.extend(0.0)),
+        ..Default::default()
+    });
+
+    louse
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::cuboid(definition.hitbox.x, definition.hitbox.y))
+        .insert(Velocity {
+            linvel: velocity,
+            angvel: thread_rng().gen_range(-LOUSE_SPIN..=LOUSE_SPIN), // random spin
+        })
+        .insert(Restitution::new(0.0))
+        .insert(ColliderMassProperties::Density(definition.density))
+        .insert(ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS)
+        .insert(ReadMassProperties::default())
+        .insert(damage::DamagingComponent {
+            damage: definition.damage,
+        })
+        .insert(LouseComponent {
+            despawn_timer: Timer::from_seconds(definition.despawn_time, TimerMode::Once),
+            on_death: definition.on_death,
+        })
+        .insert(states::AppStateComponent(states::AppStates::Game));
+
+    if let Some(on_contact) = definition.on_contact {
+        on_contact.insert_component(&mut louse, velocity);
+    }
+
+    if let Some(ability) = definition.on_activate {
+        louse.insert(InFlightAbilityComponent { ability });
+    }
+
+    if let Some(chunk_jump) = definition.chunk_jump {
+        louse.insert(chunk_jump);
+    }
+
+    louse.id()
 }
 
 #[derive(Component)]
 pub struct LouseComponent {
-    pub damage: f32,
     pub despawn_timer: Timer,
-    pub should_explode: bool,
+    pub on_death: DeathAbility,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct BasicLouseComponent {
     pub jump_range_x: (f32, f32),
     pub jump_range_y: (f32, f32),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LouseAbility {
+    // divide into three basic lice
+    Split,
+}
+
+// Ability the player can trigger while the louse is in the air, lost once it hits something
+#[derive(Component)]
+pub struct InFlightAbilityComponent {
+    pub ability: LouseAbility,
+}
+
+// Trigger the abilities of airborne lice with a second click or charge press
+pub fn louse_ability_system(
+    mut commands: Commands,
+    mut action_input: input::ActionInputMut,
+    touches: Res<Touches>,
+    mut launch_resource: ResMut<launch::LaunchResource>,
+    ability_query: Query<(&InFlightAbilityComponent, &Transform, &Velocity)>,
+    mut louse_landed_events: EventReader<collision::LouseLandedEvent>,
+    mut spawn_louse_events: EventWriter<SpawnLouseEvent>,
+) {
+    launch_resource.ability_touch_id = None;
+
+    // lice lose their ability as soon as they land
+    for event in louse_landed_events.iter() {
+        if ability_query.get(event.louse).is_ok() {
+            commands
+                .entity(event.louse)
+                .remove::<InFlightAbilityComponent>();
+        }
+    }
+
+    // only the louse fired last can use its ability
+    let entity = match launch_resource.last_launched {
+        Some(entity) => entity,
+        None => return,
+    };
+    let (ability_component, transform, velocity) = match ability_query.get(entity) {
+        Ok(louse) => louse,
+        Err(_) => return,
+    };
+
+    let tap = touches.iter_just_pressed().next().map(|touch| touch.id());
+    if !(action_input.just_pressed(InputAction::Fling)
+        || action_input.just_pressed(InputAction::Charge)
+        || tap.is_some())
+    {
+        return;
+    }
+
+    // keep the press from starting a new pull of the slingshot
+    action_input.reset(InputAction::Fling);
+    action_input.reset(InputAction::Charge);
+    launch_resource.ability_touch_id = tap;
+    launch_resource.last_launched = None;
+
+    let position = transform.translation.truncate();
+
+    match ability_component.ability {
+        LouseAbility::Split => {
+            commands.entity(entity).despawn();
+
+            let side = velocity.linvel.perp().normalize_or_zero() * SPLIT_SPACING;
+            for i in -1..=1 {
+                spawn_louse_events.send(SpawnLouseEvent {
+                    position: position + side * i as f32,
+                    velocity: Vec2::from_angle(SPLIT_ANGLE * i as f32).rotate(velocity.linvel),
+                    louse_type: LouseType::Basic,
+                    launched: false,
+                });
+            }
+        }
+    }
+}
+
+// Knocks hair segments over and hits roots harder
+#[derive(Component)]
+pub struct HeavyLouseComponent {
+    pub knockback: f32,
+    pub root_damage: f32,
+    // velocity before the last physics step, the hit has already slowed the louse down when it's handled
+    pub pre_contact_velocity: Vec2,
+}
+
+// Push hair hit by heavy lice along the louse's path and deal bonus damage to roots
+pub fn heavy_louse_system(
+    mut commands: Commands,
+    mut heavy_query: Query<(&mut HeavyLouseComponent, &Velocity)>,
+    hair_query: Query<Option<&hair::RootComponent>, With<hair::HairComponent>>,
+    mut louse_hit_hair_events: EventReader<collision::LouseHitHairEvent>,
+    mut damage_events: EventWriter<damage::DamageEvent>,
+) {
+    for event in louse_hit_hair_events.iter() {
+        let ((heavy, _), root) = match (heavy_query.get(event.louse), hair_query.get(event.hair)) {
+            (Ok(heavy), Ok(root)) => (heavy, root),
+            _ => continue,
+        };
+
+        commands.entity(event.hair).insert(ExternalImpulse {
+            impulse: heavy.pre_contact_velocity * heavy.knockback,
+            torque_impulse: 0.0,
+        });
+
+        if root.is_some() {
+            damage_events.send(damage::DamageEvent {
+                target: event.hair,
+                amount: heavy.pre_contact_velocity.length() * heavy.root_damage,
+            });
+        }
+    }
+
+    // the velocity going into the next physics step
+    for (mut heavy, velocity) in heavy_query.iter_mut() {
+        heavy.pre_contact_velocity = velocity.linvel;
+    }
+}
+
+// Latches onto the first hair segment it touches and gnaws on it
+#[derive(Component)]
+pub struct StickyLouseComponent {
+    pub latched_hair: Option<Entity>,
+    pub gnaw_damage: f32,
+    pub gnaw_timer: Timer,
+    // how long the louse keeps gnawing before it dies
+    pub latch_timer: Timer,
+}
+
+// Latch sticky lice onto hair they touch and damage the hair until the louse or the segment is gone
+#[allow(clippy::too_many_arguments)]
+pub fn sticky_louse_system(
+    mut commands: Commands,
+    mut sticky_query: Query<(
+        Entity,
+        &mut StickyLouseComponent,
+        &mut LouseComponent,
+        &Transform,
+    )>,
+    hair_query: Query<&Transform, With<hair::HairComponent>>,
+    mut louse_hit_hair_events: EventReader<collision::LouseHitHairEvent>,
+    mut damage_events: EventWriter<damage::DamageEvent>,
+    mut explosion_events: EventWriter<ExplosionEvent>,
+    time: Res<Time>,
+    game_assets: Res<GameAssets>,
+    audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
+) {
+    for event in louse_hit_hair_events.iter() {
+        let (hair_transform, (_, mut sticky, _, louse_transform)) = match (
+            hair_query.get(event.hair),
+            sticky_query.get_mut(event.louse),
+        ) {
+            (Ok(hair_transform), Ok(sticky)) => (hair_transform, sticky),
+            _ => continue,
+        };
+
+        if sticky.latched_hair.is_some() {
+            continue;
+        }
+
+        // hold the louse where it touched the segment
+        let local_anchor = hair_transform.rotation.inverse()
+            * (louse_transform.translation - hair_transform.translation);
+        let joint = FixedJointBuilder::new()
+            .local_anchor1(local_anchor.truncate())
+            .local_basis1(
+                (hair_transform.rotation.inverse() * louse_transform.rotation)
+                    .to_euler(EulerRot::ZYX)
+                    .0,
+            );
+
+        commands
+            .entity(event.louse)
+            .insert(ImpulseJoint::new(event.hair, joint));
+        sticky.latched_hair = Some(event.hair);
+    }
+
+    for (louse_entity, mut sticky, mut louse_component, louse_transform) in sticky_query.iter_mut()
+    {
+        let hair_entity = match sticky.latched_hair {
+            Some(hair_entity) => hair_entity,
+            None => continue,
+        };
+
+        // latched lice stay still, so keep them from despawning like resting lice
+        louse_component.despawn_timer.reset();
+        sticky.latch_timer.tick(time.delta());
+        sticky.gnaw_timer.tick(time.delta());
+
+        if hair_query.get(hair_entity).is_err() || sticky.latch_timer.finished() {
+            commands.entity(louse_entity).despawn();
+            louse_component.on_death.trigger(
+                &mut commands,
+                &game_assets,
+                &audio_channel,
+                &mut explosion_events,
+                louse_transform.translation.truncate(),
+            );
+        } else if sticky.gnaw_timer.just_finished() {
+            damage_events.send(damage::DamageEvent {
+                target: hair_entity,
+                amount: sticky.gnaw_damage,
+            });
+        }
+    }
+}
+
 pub fn louse_behavior_system(
     mut commands: Commands,
     mut louse_query: Query<(Entity, &mut LouseComponent, &Velocity, &Transform)>,
     time: Res<Time>,
     game_assets: Res<GameAssets>,
     audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
+    mut explosion_events: EventWriter<ExplosionEvent>,
     //mut effects: ResMut<Assets<EffectAsset>>,
 ) {
     for (louse_entity, mut louse_component, louse_vel, louse_trans) in louse_query.iter_mut() {
@@ -145,25 +632,13 @@ pub fn louse_behavior_system(
 
         if louse_component.despawn_timer.just_finished() {
             commands.entity(louse_entity).despawn();
-            if louse_component.should_explode {
-                spawn_chunk_explosion(
-                    &mut commands,
-                    game_assets.bug_parts.clone(),
-                    Vec2::new(louse_trans.translation.x, louse_trans.translation.y),
-                    15,
-                    1.0,
-                );
-                spawn_chunk_explosion(
-                    &mut commands,
-                    game_assets.bug_head.clone(),
-                    Vec2::new(louse_trans.translation.x, louse_trans.translation.y),
-                    1,
-                    1.5,
-                );
-                audio_channel.play(game_assets.bug_explode.clone());
-            } else {
-                audio_channel.play(game_assets.bug_squish.clone());
-            }
+            louse_component.on_death.trigger(
+                &mut commands,
+                &game_assets,
+                &audio_channel,
+                &mut explosion_events,
+                Vec2::new(louse_trans.translation.x, louse_trans.translation.y),
+            );
 
             /*
             spawn_effect(
@@ -175,3 +650,40 @@ pub fn louse_behavior_system(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explosion_falloff_is_full_at_center_and_fades_to_edge() {
+        assert_eq!(explosion_falloff(Vec2::ZERO, 100.0), 1.0);
+        assert!((explosion_falloff(Vec2::new(0.0, 50.0), 100.0) - 0.5).abs() < 1e-6);
+        assert_eq!(explosion_falloff(Vec2::new(100.0, 0.0), 100.0), 0.0);
+        assert_eq!(explosion_falloff(Vec2::new(300.0, 0.0), 100.0), 0.0);
+    }
+
+    #[test]
+    fn exploding_louse_damages_hair_inside_its_radius() {
+        let (radius, damage) = match LouseType::Exploding.definition().on_death {
+            DeathAbility::Explode { radius, damage, .. } => (radius, damage),
+            other => panic!("exploding louse dies with {:?}", other),
+        };
+
+        let hair_offset = Vec2::new(radius * 0.25, 0.0);
+        let hair_damage = damage * explosion_falloff(hair_offset, radius);
+        assert!((hair_damage - damage * 0.75).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sticky_louse_leaves_bug_parts_when_it_dies() {
+        assert_eq!(
+            LouseType::Sticky.definition().on_death,
+            DeathAbility::Splatter {
+                chunks: STICKY_LOUSE_DEATH_CHUNKS,
+                chunk_size: 0.5,
+            }
+        );
+        assert_eq!(LouseType::Basic.definition().on_death, DeathAbility::Squish);
+    }
+}
@@ -1,9 +1,6 @@
 use bevy::{
     prelude::*,
-    render::{
-        camera::ScalingMode,
-        settings::{WgpuFeatures, WgpuSettings},
-    },
+    render::camera::{ScalingMode, Viewport},
 };
 use bevy_asset_loader::prelude::*;
 //use bevy_hanabi::prelude::*;
@@ -12,38 +9,28 @@ use bevy_rapier2d::prelude::*;
 
 mod assets;
 mod chunks;
+mod collision;
+#[cfg(feature = "dev_console")]
+mod console;
+mod damage;
 //mod effects;
 mod hair;
+mod input;
 mod launch;
 mod level;
+mod level_select;
 mod louse;
+mod menu;
+mod save;
+mod score;
+mod settings;
 mod states;
 mod ui;
 
-const FIRE_LINE: f32 = -300.0;
 const FLOOR_Y: f32 = -200.0;
+const WINDOW_WIDTH: f32 = 854.0;
+const WINDOW_HEIGHT: f32 = 480.0;
 const GRAVITY: f32 = -550.0;
-const LOUSE_QUEUE: [louse::LouseType; 18] = [
-    louse::LouseType::Basic,
-    louse::LouseType::Basic,
-    louse::LouseType::Basic,
-    louse::LouseType::Exploding,
-    louse::LouseType::Basic,
-    louse::LouseType::Exploding,
-    louse::LouseType::Basic,
-    louse::LouseType::Basic,
-    louse::LouseType::Exploding,
-    louse::LouseType::Basic,
-    louse::LouseType::Exploding,
-    louse::LouseType::Basic,
-    louse::LouseType::Basic,
-    louse::LouseType::Exploding,
-    louse::LouseType::Basic,
-    louse::LouseType::Basic,
-    louse::LouseType::Exploding,
-    louse::LouseType::Basic,
-];
-const LOSE_TIME: f32 = 2.0;
 
 fn main() {
     /*
@@ -56,16 +43,15 @@ fn main() {
 
     app.add_plugins(DefaultPlugins.set(WindowPlugin {
         window: WindowDescriptor {
-            width: 854.0,
-            height: 480.0,
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
             // mode: WindowMode::Fullscreen,
-            resizable: false,
+            resizable: true,
             ..Default::default()
         },
         ..Default::default()
     }))
     .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
-    //.add_plugin(RapierDebugRenderPlugin::default())
     .add_plugin(AudioPlugin)
     //.add_plugin(HanabiPlugin)
     .add_audio_channel::<SoundEffectsAudioChannel>()
@@ -75,14 +61,31 @@ fn main() {
     .insert_resource(ClearColor(Color::BLACK))
     .insert_resource(launch::LaunchResource {
         velocity_multiplier: 7.0,
+        max_pull_distance: 150.0,
         ..Default::default()
     })
-    .insert_resource(level::LevelResource {
-        louse_queue: LOUSE_QUEUE.to_vec(),
-        lose_timer: Timer::from_seconds(LOSE_TIME, TimerMode::Once),
-    })
+    .init_resource::<level::LevelResource>()
+    .init_resource::<level::CurrentLevel>()
+    .insert_resource(save::load_save_data())
+    .init_resource::<score::ScoreResource>()
+    .init_resource::<input::InputMap>()
+    .init_resource::<settings::SettingsResource>()
+    .init_resource::<menu::MainMenuResource>()
+    .init_resource::<level_select::LevelSelectResource>()
+    .init_resource::<damage::PhysicsStepResource>()
+    .add_asset::<level::LevelData>()
+    .init_asset_loader::<level::LevelLoader>()
+    .add_asset::<input::InputMap>()
+    .init_asset_loader::<input::InputMapLoader>()
     .add_event::<louse::SpawnLouseEvent>()
-    .add_startup_system(setup_camera);
+    .add_event::<damage::DamageEvent>()
+    .add_event::<collision::LouseHitHairEvent>()
+    .add_event::<collision::LouseAteChunkEvent>()
+    .add_event::<collision::LouseLandedEvent>()
+    .add_event::<louse::ExplosionEvent>()
+    .add_startup_system(setup_camera)
+    .add_system(letterbox_camera_system)
+    .add_system_to_stage(PhysicsStages::Writeback, damage::physics_step_system);
 
     app.add_state(states::AppStates::LoadingMainMenu); // start game in the main menu state
     app.add_loading_state(
@@ -97,41 +100,131 @@ fn main() {
             .with_collection::<assets::MenuAssets>(),
     );
 
+    app.add_system_set(
+        SystemSet::on_exit(states::AppStates::LoadingMainMenu)
+            .with_system(input::apply_input_map_system)
+            .with_system(settings::apply_settings_system),
+    );
+
     app.add_system_set(
         SystemSet::on_enter(states::AppStates::Game)
             .with_system(setup_physics.label("init"))
-            .with_system(states::setup_game_system.after("init")),
+            .with_system(states::setup_game_system.after("init"))
+            .with_system(score::reset_score_system),
     );
 
     app.add_system_set(
         SystemSet::on_update(states::AppStates::Game)
-            .with_system(launch::fling_louse_system.label("fling_louse"))
+            .with_system(collision::collision_dispatch_system.label("collisions"))
+            .with_system(
+                louse::louse_ability_system
+                    .label("game_input")
+                    .after("collisions")
+                    .before("fling_louse"),
+            )
+            .with_system(
+                launch::fling_louse_system
+                    .label("fling_louse")
+                    .label("game_input"),
+            )
+            .with_system(
+                launch::aim_louse_system
+                    .label("fling_louse")
+                    .label("game_input"),
+            )
+            .with_system(
+                launch::touch_fling_louse_system
+                    .label("fling_louse")
+                    .label("game_input"),
+            )
             .with_system(louse::spawn_louse_system.after("fling_louse"))
-            .with_system(states::start_gameover_system)
-            .with_system(states::start_victory_system)
+            .with_system(launch::trajectory_preview_system.after("fling_louse"))
+            .with_system(launch::slingshot_system.after("fling_louse"))
+            .with_system(states::start_pause_menu_system.label("game_input"))
             .with_system(hair::hair_system)
+            .with_system(damage::contact_damage_system.before(hair::hair_system))
+            .with_system(hair::hair_regrowth_system.after(hair::hair_system))
+            .with_system(hair::hair_growing_system)
             .with_system(hair::check_roots_system)
             .with_system(level::lose_system)
+            .with_system(score::score_system)
             .with_system(louse::louse_behavior_system)
-            .with_system(chunks::chunk_system)
+            .with_system(
+                louse::explosion_system
+                    .after(louse::louse_behavior_system)
+                    .before(hair::hair_system),
+            )
+            .with_system(
+                louse::sticky_louse_system
+                    .after("collisions")
+                    .before(hair::hair_system),
+            )
+            .with_system(
+                louse::heavy_louse_system
+                    .after("collisions")
+                    .before(hair::hair_system),
+            )
+            .with_system(chunks::chunk_system.after("collisions"))
             .with_system(ui::game_ui_system), //.with_system(effects::effect_system),
     );
 
+    #[cfg(feature = "dev_console")]
+    {
+        app.add_plugin(RapierDebugRenderPlugin {
+            enabled: false,
+            ..Default::default()
+        })
+        .init_resource::<console::ConsoleResource>();
+
+        app.add_system_set(
+            SystemSet::on_update(states::AppStates::Game)
+                // everything reading input in the game waits for the console to swallow typed keys
+                .before("game_input")
+                .with_system(console::console_input_system)
+                .with_system(console::console_ui_system),
+        );
+
+        app.add_system_set(
+            SystemSet::on_exit(states::AppStates::Game).with_system(console::close_console_system),
+        );
+    }
+
     app.add_system_set(
         SystemSet::on_exit(states::AppStates::Game)
             .with_system(states::clean_up_game_system)
             .with_system(states::clear_state_system),
     );
 
+    app.add_system_set(
+        SystemSet::on_pause(states::AppStates::Game).with_system(states::pause_game_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_resume(states::AppStates::Game).with_system(states::resume_game_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_enter(states::AppStates::PauseMenu)
+            .with_system(states::setup_pause_menu_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_update(states::AppStates::PauseMenu).with_system(states::pause_menu_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_exit(states::AppStates::PauseMenu).with_system(states::clear_state_system),
+    );
+
     app.add_system_set(
         SystemSet::on_enter(states::AppStates::MainMenu)
-            .with_system(states::setup_main_menu_system),
+            .with_system(states::setup_main_menu_system)
+            .with_system(menu::setup_menu_buttons_system),
     );
 
     app.add_system_set(
         SystemSet::on_update(states::AppStates::MainMenu)
-            .with_system(states::start_instructions_system)
-            .with_system(states::start_credits_system)
+            .with_system(menu::main_menu_system)
             .with_system(states::quit_game_system),
     );
 
@@ -158,9 +251,27 @@ fn main() {
             .with_system(states::clean_up_gameover_menu_system),
     );
 
+    app.add_system_set(
+        SystemSet::on_enter(states::AppStates::LevelComplete)
+            .with_system(states::setup_level_complete_system)
+            .with_system(save::record_level_result_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_update(states::AppStates::LevelComplete)
+            .with_system(states::start_next_level_system)
+            .with_system(states::quit_game_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_exit(states::AppStates::LevelComplete)
+            .with_system(states::clear_state_system),
+    );
+
     app.add_system_set(
         SystemSet::on_enter(states::AppStates::Victory)
             .with_system(states::setup_victory_menu_system)
+            .with_system(save::record_level_result_system)
             .with_system(states::setup_dance_lice),
     );
 
@@ -174,12 +285,14 @@ fn main() {
     app.add_system_set(
         SystemSet::on_exit(states::AppStates::Victory)
             .with_system(states::clear_state_system)
-            .with_system(states::clean_up_victory_menu_system),
+            .with_system(states::clean_up_victory_menu_system)
+            .with_system(level::reset_current_level_system),
     );
 
     app.add_system_set(
         SystemSet::on_enter(states::AppStates::Instructions)
-            .with_system(states::setup_instructions_system),
+            .with_system(states::setup_instructions_system)
+            .with_system(level::reset_current_level_system),
     );
 
     app.add_system_set(
@@ -204,6 +317,33 @@ fn main() {
         SystemSet::on_exit(states::AppStates::Credits).with_system(states::clear_state_system),
     );
 
+    app.add_system_set(
+        SystemSet::on_enter(states::AppStates::LevelSelect)
+            .with_system(level_select::setup_level_select_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_update(states::AppStates::LevelSelect)
+            .with_system(level_select::level_select_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_exit(states::AppStates::LevelSelect).with_system(states::clear_state_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_enter(states::AppStates::Settings)
+            .with_system(settings::setup_settings_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_update(states::AppStates::Settings).with_system(settings::settings_system),
+    );
+
+    app.add_system_set(
+        SystemSet::on_exit(states::AppStates::Settings).with_system(states::clear_state_system),
+    );
+
     app.run();
 }
 
@@ -225,8 +365,8 @@ fn setup_camera(mut commands: Commands) {
         .spawn(Camera2dBundle {
             transform: Transform::from_xyz(0.0, 0.0, 200.0),
             projection: OrthographicProjection {
-                //scale: 1.0,
-                //scaling_mode: ScalingMode::FixedVertical(1.),
+                // the world is always WINDOW_HEIGHT units tall, the viewport keeps the width in proportion
+                scaling_mode: ScalingMode::FixedVertical(WINDOW_HEIGHT),
                 ..Default::default()
             },
             ..default()
@@ -234,6 +374,51 @@ fn setup_camera(mut commands: Commands) {
         .insert(MainCamera);
 }
 
+// keep the camera's viewport at the original aspect ratio, centered with black bars around it
+fn letterbox_camera_system(
+    windows: Res<Windows>,
+    mut camera_query: Query<&mut Camera, With<MainCamera>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    if window_size.x == 0 || window_size.y == 0 {
+        return;
+    }
+
+    let aspect_ratio = WINDOW_WIDTH / WINDOW_HEIGHT;
+    let viewport_size = if window_size.x as f32 / window_size.y as f32 > aspect_ratio {
+        UVec2::new(
+            (window_size.y as f32 * aspect_ratio).round() as u32,
+            window_size.y,
+        )
+    } else {
+        UVec2::new(
+            window_size.x,
+            (window_size.x as f32 / aspect_ratio).round() as u32,
+        )
+    };
+    let viewport_position = (window_size - viewport_size) / 2;
+
+    for mut camera in camera_query.iter_mut() {
+        let unchanged = camera.viewport.as_ref().is_some_and(|viewport| {
+            viewport.physical_position == viewport_position
+                && viewport.physical_size == viewport_size
+        });
+
+        if !unchanged {
+            camera.viewport = Some(Viewport {
+                physical_position: viewport_position,
+                physical_size: viewport_size,
+                ..Default::default()
+            });
+        }
+    }
+}
+
 // setup the 2D Rapier physics
 fn setup_physics(mut rapier_config: ResMut<RapierConfiguration>) {
     rapier_config.physics_pipeline_active = true;
@@ -0,0 +1,270 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    assets,
+    input::{self, InputAction},
+    launch,
+    states::{AppStateComponent, AppStates},
+};
+
+const MENU_BUTTON_X: f32 = 150.0;
+const MENU_BUTTON_TOP_Y: f32 = -30.0;
+const MENU_BUTTON_SPACING: f32 = 47.0;
+const MENU_BUTTON_SCALE: f32 = 0.8;
+// size of the button images
+const MENU_BUTTON_SIZE: Vec2 = Vec2 { x: 169.0, y: 51.0 };
+const MENU_GO_BUTTON_SCALE: f32 = 0.5;
+const MENU_LABEL_FONT_SIZE: f32 = 22.0;
+// covers the key prompts drawn into the title image
+const MENU_PANEL_RECT: bevy::math::Rect = bevy::math::Rect {
+    min: Vec2 { x: 5.0, y: -240.0 },
+    max: Vec2 { x: 295.0, y: -5.0 },
+};
+const MENU_PANEL_COLOR: Color = Color::rgba(0.25, 0.14, 0.08, 0.85);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuButton {
+    Start,
+    LevelSelect,
+    Settings,
+    Credits,
+    Quit,
+}
+
+impl MenuButton {
+    // in order from the top of the menu
+    pub const ALL: [MenuButton; 5] = [
+        MenuButton::Start,
+        MenuButton::LevelSelect,
+        MenuButton::Settings,
+        MenuButton::Credits,
+        MenuButton::Quit,
+    ];
+
+    // text drawn next to buttons whose image has no label
+    fn label(&self) -> Option<&'static str> {
+        match self {
+            MenuButton::Start | MenuButton::Credits => None,
+            MenuButton::LevelSelect => Some("level select"),
+            MenuButton::Settings => Some("settings"),
+            MenuButton::Quit => Some("quit"),
+        }
+    }
+
+    // shortcut that activates the button without selecting it
+    fn shortcut(&self) -> Option<InputAction> {
+        match self {
+            MenuButton::Credits => Some(InputAction::Credits),
+            MenuButton::Settings => Some(InputAction::Settings),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MenuButtonComponent {
+    pub button: MenuButton,
+    pub default_image: Handle<Image>,
+    pub selected_image: Handle<Image>,
+    // clickable area around the button's position
+    pub size: Vec2,
+}
+
+// Selected main menu button and the last cursor position used for hovering
+#[derive(Resource, Default)]
+pub struct MainMenuResource {
+    pub selected: usize,
+    pub last_cursor_position: Option<Vec2>,
+}
+
+pub fn setup_menu_buttons_system(
+    mut commands: Commands,
+    menu_assets: Res<assets::MenuAssets>,
+    mut main_menu_resource: ResMut<MainMenuResource>,
+) {
+    *main_menu_resource = MainMenuResource::default();
+
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: MENU_PANEL_COLOR,
+                custom_size: Some(MENU_PANEL_RECT.size()),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(MENU_PANEL_RECT.center().extend(1.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::MainMenu));
+
+    for (i, button) in MenuButton::ALL.iter().enumerate() {
+        let y = MENU_BUTTON_TOP_Y - i as f32 * MENU_BUTTON_SPACING;
+
+        let (default_image, selected_image) = match button {
+            MenuButton::Start => (
+                menu_assets.menu_button_start.clone(),
+                menu_assets.menu_button_start_selected.clone(),
+            ),
+            MenuButton::Credits => (
+                menu_assets.menu_button_credits.clone(),
+                menu_assets.menu_button_credits_selected.clone(),
+            ),
+            _ => (
+                menu_assets.button_go_default.clone(),
+                menu_assets.button_go_selected.clone(),
+            ),
+        };
+
+        // labelled buttons are a small go button to the right of their text
+        let (position, scale) = if button.label().is_some() {
+            (
+                Vec2::new(
+                    MENU_PANEL_RECT.max.x - MENU_BUTTON_SIZE.x * MENU_GO_BUTTON_SCALE / 2.0 - 10.0,
+                    y,
+                ),
+                MENU_GO_BUTTON_SCALE,
+            )
+        } else {
+            (Vec2::new(MENU_BUTTON_X, y), MENU_BUTTON_SCALE)
+        };
+
+        if let Some(label) = button.label() {
+            commands
+                .spawn(Text2dBundle {
+                    text: Text::from_section(
+                        label,
+                        TextStyle {
+                            font: menu_assets.font.clone(),
+                            font_size: MENU_LABEL_FONT_SIZE,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER_LEFT),
+                    transform: Transform::from_xyz(MENU_PANEL_RECT.min.x + 15.0, y, 2.0),
+                    ..Default::default()
+                })
+                .insert(AppStateComponent(AppStates::MainMenu));
+        }
+
+        commands
+            .spawn(SpriteBundle {
+                texture: default_image.clone(),
+                transform: Transform {
+                    translation: position.extend(2.0),
+                    scale: Vec3::new(scale, scale, 1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(MenuButtonComponent {
+                button: *button,
+                default_image,
+                selected_image,
+                // labelled buttons can be clicked anywhere on their row
+                size: if button.label().is_some() {
+                    Vec2::new(MENU_PANEL_RECT.width(), MENU_BUTTON_SIZE.y * scale)
+                } else {
+                    MENU_BUTTON_SIZE * scale
+                },
+            })
+            .insert(AppStateComponent(AppStates::MainMenu));
+    }
+}
+
+// Select buttons with the cursor or up and down, and press them with a click, tap or confirm
+#[allow(clippy::too_many_arguments)]
+pub fn main_menu_system(
+    mut action_input: input::ActionInputMut,
+    mut main_menu_resource: ResMut<MainMenuResource>,
+    mut button_query: Query<(&MenuButtonComponent, &Transform, &mut Handle<Image>)>,
+    mut app_state: ResMut<State<AppStates>>,
+    mut app_exit_events: EventWriter<AppExit>,
+    windows: Res<Windows>,
+    camera: Query<(&Camera, &GlobalTransform), With<crate::MainCamera>>,
+    touches: Res<Touches>,
+) {
+    let window = windows.get_primary().unwrap();
+    let (camera, camera_gl_transform) = camera.single();
+    let cursor_position = launch::get_cursor_physics_position(camera, camera_gl_transform, window);
+    let tap_position =
+        launch::get_tap_physics_position(camera, camera_gl_transform, window, &touches);
+
+    let button_at = |position: Vec2| {
+        button_query
+            .iter()
+            .find(|(button, transform, _)| {
+                bevy::math::Rect::from_center_size(
+                    // labelled rows are clickable across the whole panel
+                    Vec2::new(
+                        if button.button.label().is_some() {
+                            MENU_PANEL_RECT.center().x
+                        } else {
+                            transform.translation.x
+                        },
+                        transform.translation.y,
+                    ),
+                    button.size,
+                )
+                .contains(position)
+            })
+            .map(|(button, _, _)| button.button)
+    };
+
+    let mut pressed_button = None;
+
+    // only hover when the cursor moves so it doesn't fight the keyboard
+    if cursor_position != main_menu_resource.last_cursor_position {
+        main_menu_resource.last_cursor_position = cursor_position;
+        if let Some(hovered) = cursor_position.and_then(button_at) {
+            main_menu_resource.selected = MenuButton::ALL
+                .iter()
+                .position(|button| *button == hovered)
+                .unwrap();
+        }
+    }
+
+    if action_input.mouse_button().just_released(MouseButton::Left) {
+        pressed_button = cursor_position.and_then(button_at);
+    }
+
+    if let Some(tapped) = tap_position.and_then(button_at) {
+        pressed_button = Some(tapped);
+    }
+
+    if action_input.just_pressed(InputAction::Up) {
+        main_menu_resource.selected =
+            (main_menu_resource.selected + MenuButton::ALL.len() - 1) % MenuButton::ALL.len();
+    } else if action_input.just_pressed(InputAction::Down) {
+        main_menu_resource.selected = (main_menu_resource.selected + 1) % MenuButton::ALL.len();
+    }
+
+    if action_input.just_released(InputAction::Confirm) {
+        pressed_button = Some(MenuButton::ALL[main_menu_resource.selected]);
+        action_input.reset(InputAction::Confirm);
+    }
+
+    for button in MenuButton::ALL.iter() {
+        if let Some(action) = button.shortcut() {
+            if action_input.just_released(action) {
+                pressed_button = Some(*button);
+                action_input.reset(action);
+            }
+        }
+    }
+
+    for (button, _, mut image) in button_query.iter_mut() {
+        *image = if button.button == MenuButton::ALL[main_menu_resource.selected] {
+            button.selected_image.clone()
+        } else {
+            button.default_image.clone()
+        };
+    }
+
+    match pressed_button {
+        Some(MenuButton::Start) => app_state.set(AppStates::Instructions).unwrap(),
+        Some(MenuButton::LevelSelect) => app_state.set(AppStates::LevelSelect).unwrap(),
+        Some(MenuButton::Settings) => app_state.set(AppStates::Settings).unwrap(),
+        Some(MenuButton::Credits) => app_state.set(AppStates::Credits).unwrap(),
+        Some(MenuButton::Quit) => app_exit_events.send(AppExit),
+        None => {}
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{assets, input, level, score};
+
+// bump this when the layout of SaveData changes and add a migration in parse_save_data
+pub const SAVE_VERSION: u32 = 1;
+
+const SAVE_NAME: &str = "root_seeker_save";
+
+// Everything that persists between runs of the game
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+pub struct SaveData {
+    pub version: u32,
+    // number of campaign levels that can be played, the first level is always unlocked
+    pub unlocked_levels: usize,
+    // best result for each cleared level, keyed by level index
+    pub best_results: BTreeMap<usize, LevelResult>,
+    #[serde(default)]
+    pub settings: Settings,
+    // set when the save on disk is from a newer version of the game, so it isn't overwritten
+    #[serde(skip)]
+    pub read_only: bool,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        SaveData {
+            version: SAVE_VERSION,
+            unlocked_levels: 1,
+            best_results: BTreeMap::new(),
+            settings: Settings::default(),
+            read_only: false,
+        }
+    }
+}
+
+impl SaveData {
+    // keep the result if it beats the previous best for the level
+    pub fn record_result(&mut self, level_index: usize, result: LevelResult) {
+        let is_best = match self.best_results.get(&level_index) {
+            Some(best) => result.is_better_than(best),
+            None => true,
+        };
+
+        if is_best {
+            self.best_results.insert(level_index, result);
+        }
+    }
+
+    pub fn unlock_level(&mut self, level_index: usize) {
+        self.unlocked_levels = self.unlocked_levels.max(level_index + 1);
+    }
+
+    pub fn is_unlocked(&self, level_index: usize) -> bool {
+        level_index < self.unlocked_levels
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelResult {
+    pub lice_remaining: usize,
+    pub time_taken: f32,
+    #[serde(default)]
+    pub stars: u32,
+}
+
+impl LevelResult {
+    // more lice left over wins, ties are broken by the faster time
+    pub fn is_better_than(&self, other: &LevelResult) -> bool {
+        self.lice_remaining > other.lice_remaining
+            || (self.lice_remaining == other.lice_remaining && self.time_taken < other.time_taken)
+    }
+}
+
+// User adjustable settings
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub master_volume: f64,
+    pub music_volume: f64,
+    pub sfx_volume: f64,
+    pub fullscreen: bool,
+    // window size when not fullscreen
+    #[serde(default = "default_resolution")]
+    pub resolution: (f32, f32),
+    // rebound controls, the default bindings are used when there are none
+    #[serde(default)]
+    pub bindings: Option<input::InputMap>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            fullscreen: false,
+            resolution: default_resolution(),
+            bindings: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn music_channel_volume(&self) -> f64 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn sfx_channel_volume(&self) -> f64 {
+        self.master_volume * self.sfx_volume
+    }
+}
+
+fn default_resolution() -> (f32, f32) {
+    (crate::WINDOW_WIDTH, crate::WINDOW_HEIGHT)
+}
+
+// only the version is read first so older layouts can be migrated, saves from before versioning have none
+#[derive(Deserialize)]
+struct SaveHeader {
+    #[serde(default)]
+    version: u32,
+}
+
+// Layout of saves written before the version field existed
+#[derive(Deserialize)]
+struct SaveDataV0 {
+    unlocked_levels: usize,
+    best_results: BTreeMap<usize, LevelResult>,
+}
+
+impl From<SaveDataV0> for SaveData {
+    fn from(old: SaveDataV0) -> Self {
+        SaveData {
+            unlocked_levels: old.unlocked_levels,
+            best_results: old.best_results,
+            ..Default::default()
+        }
+    }
+}
+
+// Parse saved data of the given version, converting older layouts up to the current one
+fn migrate(version: u32, contents: &str) -> Result<SaveData, ron::error::SpannedError> {
+    match version {
+        0 => ron::from_str::<SaveDataV0>(contents).map(SaveData::from),
+        // when bumping SAVE_VERSION, keep the old layout as SaveDataV1 and convert it here
+        _ => ron::from_str(contents),
+    }
+}
+
+// Parse saved data, migrating it from older versions
+fn parse_save_data(contents: &str) -> Result<SaveData, ron::error::SpannedError> {
+    let header: SaveHeader = ron::from_str(contents)?;
+
+    if header.version > SAVE_VERSION {
+        warn!(
+            "save data version {} is newer than {}, progress won't be saved",
+            header.version, SAVE_VERSION
+        );
+        return Ok(SaveData {
+            read_only: true,
+            ..Default::default()
+        });
+    }
+
+    migrate(header.version, contents)
+}
+
+// Load the save data, falling back to a new save if there is none or it can't be read
+pub fn load_save_data() -> SaveData {
+    match read_save_string() {
+        Some(contents) => match parse_save_data(&contents) {
+            Ok(save_data) => save_data,
+            Err(err) => {
+                warn!("could not parse save data: {}", err);
+                SaveData::default()
+            }
+        },
+        None => SaveData::default(),
+    }
+}
+
+// Write the save data to storage
+pub fn store_save_data(save_data: &SaveData) {
+    if save_data.read_only {
+        return;
+    }
+
+    match ron::ser::to_string_pretty(save_data, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => write_save_string(&contents),
+        Err(err) => warn!("could not serialize save data: {}", err),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_path() -> Option<std::path::PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("root_seeker").join(format!("{}.ron", SAVE_NAME)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_save_string() -> Option<String> {
+    std::fs::read_to_string(save_path()?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_save_string(contents: &str) {
+    let path = match save_path() {
+        Some(path) => path,
+        None => {
+            warn!("no config directory to write save data to");
+            return;
+        }
+    };
+
+    if let Some(parent) = path.parent() {
+        if let Err(err) = std::fs::create_dir_all(parent) {
+            warn!("could not create save directory: {}", err);
+            return;
+        }
+    }
+
+    if let Err(err) = std::fs::write(&path, contents) {
+        warn!("could not write save data: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_save_string() -> Option<String> {
+    local_storage()?.get_item(SAVE_NAME).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_save_string(contents: &str) {
+    let storage = match local_storage() {
+        Some(storage) => storage,
+        None => {
+            warn!("no local storage to write save data to");
+            return;
+        }
+    };
+
+    if storage.set_item(SAVE_NAME, contents).is_err() {
+        warn!("could not write save data to local storage");
+    }
+}
+
+// Record the result of the cleared level and unlock the next one
+pub fn record_level_result_system(
+    mut save_data: ResMut<SaveData>,
+    score: Res<score::ScoreResource>,
+    current_level: Res<level::CurrentLevel>,
+    menu_assets: Res<assets::MenuAssets>,
+    level_data_assets: Res<Assets<level::LevelData>>,
+) {
+    let level_data = level_data_assets
+        .get(&menu_assets.levels[current_level.0])
+        .unwrap();
+
+    save_data.record_result(
+        current_level.0,
+        LevelResult {
+            lice_remaining: score.lice_remaining,
+            time_taken: score.elapsed_time,
+            stars: score.stars(level_data.star_thresholds),
+        },
+    );
+
+    if current_level.0 + 1 < menu_assets.levels.len() {
+        save_data.unlock_level(current_level.0 + 1);
+    }
+
+    store_save_data(&save_data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_unversioned_save() {
+        let save_data = parse_save_data(include_str!("fixtures/save_v0.ron")).unwrap();
+
+        assert_eq!(save_data.version, SAVE_VERSION);
+        assert_eq!(save_data.unlocked_levels, 3);
+        assert_eq!(save_data.best_results[&0].lice_remaining, 4);
+        assert_eq!(save_data.best_results[&1].stars, 0);
+        assert_eq!(save_data.settings.master_volume, 1.0);
+        assert!(!save_data.read_only);
+    }
+
+    #[test]
+    fn parses_version_1_save() {
+        let save_data = parse_save_data(include_str!("fixtures/save_v1.ron")).unwrap();
+
+        assert_eq!(save_data.unlocked_levels, 2);
+        assert_eq!(save_data.best_results[&0].stars, 3);
+        assert_eq!(save_data.settings.music_channel_volume(), 0.4);
+        assert_eq!(save_data.settings.resolution, (1280.0, 720.0));
+        assert_eq!(
+            save_data
+                .settings
+                .bindings
+                .unwrap()
+                .get(input::InputAction::Fling),
+            &[input::InputBinding::Mouse(MouseButton::Right)]
+        );
+        assert!(!save_data.read_only);
+    }
+
+    #[test]
+    fn newer_save_is_not_overwritten() {
+        let save_data = parse_save_data(include_str!("fixtures/save_newer.ron")).unwrap();
+
+        assert!(save_data.read_only);
+        assert_eq!(save_data.unlocked_levels, 1);
+    }
+
+    #[test]
+    fn current_save_round_trips() {
+        let mut save_data = SaveData::default();
+        save_data.unlock_level(4);
+        save_data.record_result(
+            2,
+            LevelResult {
+                lice_remaining: 1,
+                time_taken: 30.0,
+                stars: 2,
+            },
+        );
+
+        let contents =
+            ron::ser::to_string_pretty(&save_data, ron::ser::PrettyConfig::default()).unwrap();
+        let parsed = parse_save_data(&contents).unwrap();
+
+        assert_eq!(parsed.unlocked_levels, 5);
+        assert_eq!(parsed.best_results[&2].stars, 2);
+    }
+
+    #[test]
+    fn keeps_the_better_result() {
+        let mut save_data = SaveData::default();
+        let result = |lice_remaining, time_taken| LevelResult {
+            lice_remaining,
+            time_taken,
+            stars: 0,
+        };
+
+        save_data.record_result(0, result(2, 30.0));
+        save_data.record_result(0, result(1, 10.0));
+        assert_eq!(save_data.best_results[&0].lice_remaining, 2);
+
+        save_data.record_result(0, result(2, 20.0));
+        assert_eq!(save_data.best_results[&0].time_taken, 20.0);
+    }
+}
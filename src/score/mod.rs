@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets,
+    level::{self, LevelResource},
+    states::{AppStateComponent, AppStates},
+};
+
+const POINTS_PER_LOUSE: u32 = 1000;
+const POINTS_PER_SEGMENT: u32 = 100;
+const POINTS_PER_CHUNK: u32 = 50;
+const POINTS_LOST_PER_SECOND: f32 = 10.0;
+
+// Tracks how well the current level is being played
+#[derive(Resource, Default, Debug)]
+pub struct ScoreResource {
+    pub lice_remaining: usize,
+    pub segments_destroyed: u32,
+    pub chunks_eaten: u32,
+    pub elapsed_time: f32,
+}
+
+impl ScoreResource {
+    pub fn points(&self) -> u32 {
+        let points = self.lice_remaining as u32 * POINTS_PER_LOUSE
+            + self.segments_destroyed * POINTS_PER_SEGMENT
+            + self.chunks_eaten * POINTS_PER_CHUNK;
+
+        points.saturating_sub((self.elapsed_time * POINTS_LOST_PER_SECOND) as u32)
+    }
+
+    // one star for clearing the level, more for beating the level's thresholds
+    pub fn stars(&self, star_thresholds: [u32; 2]) -> u32 {
+        let points = self.points();
+        1 + star_thresholds
+            .iter()
+            .filter(|threshold| points >= **threshold)
+            .count() as u32
+    }
+}
+
+pub fn reset_score_system(mut score: ResMut<ScoreResource>) {
+    *score = ScoreResource::default();
+}
+
+// Keep the time and unused lice of the score up to date
+pub fn score_system(
+    mut score: ResMut<ScoreResource>,
+    level_resource: Res<LevelResource>,
+    time: Res<Time>,
+) {
+    score.elapsed_time += time.delta_seconds();
+    score.lice_remaining = level_resource.louse_queue.len();
+}
+
+// Spawn the star rating and points for the cleared level
+pub fn spawn_score_text(
+    commands: &mut Commands,
+    menu_assets: &assets::MenuAssets,
+    score: &ScoreResource,
+    level_data: &level::LevelData,
+    position: Vec2,
+    color: Color,
+    app_state: AppStates,
+) {
+    let stars = score.stars(level_data.star_thresholds);
+    let star_text: String = (0..3)
+        .map(|i| if i < stars { '\u{2605}' } else { '\u{2606}' })
+        .collect();
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                star_text,
+                TextStyle {
+                    font: menu_assets.font.clone(),
+                    font_size: 48.0,
+                    color: Color::GOLD,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(position.extend(1.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(app_state.clone()));
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                format!(
+                    "{} points\n{} lice left, {} hair destroyed, {} chunks eaten, {:.1}s",
+                    score.points(),
+                    score.lice_remaining,
+                    score.segments_destroyed,
+                    score.chunks_eaten,
+                    score.elapsed_time
+                ),
+                TextStyle {
+                    font: menu_assets.font.clone(),
+                    font_size: 16.0,
+                    color,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(Vec3::new(position.x, position.y - 50.0, 1.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(app_state));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_add_up_and_drop_over_time() {
+        let score = ScoreResource {
+            lice_remaining: 2,
+            segments_destroyed: 5,
+            chunks_eaten: 4,
+            elapsed_time: 10.0,
+        };
+        assert_eq!(score.points(), 2000 + 500 + 200 - 100);
+
+        let slow_score = ScoreResource {
+            elapsed_time: 1000.0,
+            ..score
+        };
+        assert_eq!(slow_score.points(), 0);
+    }
+
+    #[test]
+    fn stars_count_thresholds_reached() {
+        let score = |lice_remaining| ScoreResource {
+            lice_remaining,
+            ..Default::default()
+        };
+        let thresholds = [3000, 6000];
+
+        assert_eq!(score(0).stars(thresholds), 1);
+        assert_eq!(score(2).stars(thresholds), 1);
+        assert_eq!(score(3).stars(thresholds), 2);
+        assert_eq!(score(6).stars(thresholds), 3);
+        assert_eq!(score(10).stars(thresholds), 3);
+    }
+}
@@ -0,0 +1,328 @@
+use bevy::{prelude::*, window::WindowMode};
+use bevy_kira_audio::prelude::*;
+
+use crate::{
+    assets,
+    input::{self, InputAction},
+    save,
+    states::{AppStateComponent, AppStates},
+};
+
+const SETTINGS_FONT_SIZE: f32 = 16.0;
+const SETTINGS_LINE_COLOR: Color = Color::WHITE;
+const SETTINGS_SELECTED_COLOR: Color = Color::GOLD;
+
+const VOLUME_STEP: f64 = 0.1;
+const VOLUME_SLIDER_STEPS: usize = 10;
+
+// window sizes that can be picked when not fullscreen
+const RESOLUTIONS: [(f32, f32); 4] = [
+    (crate::WINDOW_WIDTH, crate::WINDOW_HEIGHT),
+    (1280.0, 720.0),
+    (1600.0, 900.0),
+    (1920.0, 1080.0),
+];
+
+// Lines of the settings screen, from the top
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettingsRow {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    WindowMode,
+    Resolution,
+    Binding(InputAction),
+}
+
+impl SettingsRow {
+    fn all() -> Vec<SettingsRow> {
+        let mut rows = vec![
+            SettingsRow::MasterVolume,
+            SettingsRow::MusicVolume,
+            SettingsRow::SfxVolume,
+            SettingsRow::WindowMode,
+            SettingsRow::Resolution,
+        ];
+        rows.extend(
+            InputAction::ALL
+                .iter()
+                .map(|action| SettingsRow::Binding(*action)),
+        );
+        rows
+    }
+}
+
+// Selected line of the settings screen and whether the next input is captured as a binding
+#[derive(Resource, Default)]
+pub struct SettingsResource {
+    pub selected: usize,
+    pub capturing: bool,
+}
+
+#[derive(Component)]
+pub struct SettingsText;
+
+pub fn setup_settings_system(
+    mut commands: Commands,
+    menu_assets: Res<assets::MenuAssets>,
+    mut settings_resource: ResMut<SettingsResource>,
+) {
+    *settings_resource = SettingsResource::default();
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "Settings",
+                TextStyle {
+                    font: menu_assets.font.clone(),
+                    font_size: 36.0,
+                    color: SETTINGS_LINE_COLOR,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_xyz(0.0, 210.0, 1.0),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::Settings));
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::default().with_alignment(TextAlignment::TOP_CENTER),
+            transform: Transform::from_xyz(0.0, 180.0, 1.0),
+            ..Default::default()
+        })
+        .insert(SettingsText)
+        .insert(AppStateComponent(AppStates::Settings));
+}
+
+// Pick a line with up and down, change it with left, right or confirm and press any input to rebind
+#[allow(clippy::too_many_arguments)]
+pub fn settings_system(
+    mut action_input: input::ActionInputMut,
+    mut settings_resource: ResMut<SettingsResource>,
+    mut save_data: ResMut<save::SaveData>,
+    mut app_state: ResMut<State<AppStates>>,
+    mut text_query: Query<&mut Text, With<SettingsText>>,
+    menu_assets: Res<assets::MenuAssets>,
+    mut windows: ResMut<Windows>,
+    menu_music_channel: Res<AudioChannel<crate::MenuMusicAudioChannel>>,
+    game_music_channel: Res<AudioChannel<crate::GameMusicAudioChannel>>,
+    sfx_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
+) {
+    let rows = SettingsRow::all();
+    let selected_row = rows[settings_resource.selected];
+
+    let step = if action_input.just_pressed(InputAction::Left) {
+        -1
+    } else if action_input.just_pressed(InputAction::Right) {
+        1
+    } else {
+        0
+    };
+
+    let mut settings_changed = false;
+
+    if settings_resource.capturing {
+        if action_input.just_pressed(InputAction::Back) {
+            // keep the old binding
+            settings_resource.capturing = false;
+            action_input.reset(InputAction::Back);
+        } else if let Some(binding) = action_input.any_just_pressed() {
+            if let SettingsRow::Binding(action) = selected_row {
+                action_input.rebind(action, binding);
+            }
+            settings_resource.capturing = false;
+        }
+    } else if action_input.just_released(InputAction::Back) {
+        save_data.settings.bindings = Some(action_input.input_map().clone());
+        save::store_save_data(&save_data);
+        app_state.set(AppStates::MainMenu).unwrap();
+        action_input.reset(InputAction::Back);
+    } else if action_input.just_pressed(InputAction::Up) {
+        settings_resource.selected = (settings_resource.selected + rows.len() - 1) % rows.len();
+    } else if action_input.just_pressed(InputAction::Down) {
+        settings_resource.selected = (settings_resource.selected + 1) % rows.len();
+    } else {
+        let confirm = action_input.just_released(InputAction::Confirm);
+        let settings = &mut save_data.settings;
+
+        match selected_row {
+            SettingsRow::MasterVolume | SettingsRow::MusicVolume | SettingsRow::SfxVolume
+                if step != 0 =>
+            {
+                let volume = match selected_row {
+                    SettingsRow::MasterVolume => &mut settings.master_volume,
+                    SettingsRow::MusicVolume => &mut settings.music_volume,
+                    _ => &mut settings.sfx_volume,
+                };
+                *volume = (*volume + step as f64 * VOLUME_STEP).clamp(0.0, 1.0);
+                settings_changed = true;
+            }
+            SettingsRow::WindowMode if confirm || step != 0 => {
+                settings.fullscreen = !settings.fullscreen;
+                settings_changed = true;
+            }
+            SettingsRow::Resolution if confirm || step != 0 => {
+                let current = RESOLUTIONS
+                    .iter()
+                    .position(|resolution| *resolution == settings.resolution)
+                    .unwrap_or(0);
+                let next = if step < 0 {
+                    current + RESOLUTIONS.len() - 1
+                } else {
+                    current + 1
+                } % RESOLUTIONS.len();
+                settings.resolution = RESOLUTIONS[next];
+                settings_changed = true;
+            }
+            SettingsRow::Binding(_) if confirm => {
+                settings_resource.capturing = true;
+            }
+            _ => {}
+        }
+    }
+
+    if settings_changed {
+        apply_volume(
+            &save_data.settings,
+            &menu_music_channel,
+            &game_music_channel,
+            &sfx_channel,
+        );
+        apply_window(&save_data.settings, &mut windows);
+    }
+
+    let settings = &save_data.settings;
+    let input_map = action_input.input_map();
+    let mut sections: Vec<TextSection> = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let is_selected = i == settings_resource.selected;
+            let line = match row {
+                SettingsRow::MasterVolume => {
+                    format!("Master volume: {}", volume_slider(settings.master_volume))
+                }
+                SettingsRow::MusicVolume => {
+                    format!("Music volume: {}", volume_slider(settings.music_volume))
+                }
+                SettingsRow::SfxVolume => {
+                    format!(
+                        "Sound effects volume: {}",
+                        volume_slider(settings.sfx_volume)
+                    )
+                }
+                SettingsRow::WindowMode => format!(
+                    "Window mode: {}",
+                    if settings.fullscreen {
+                        "fullscreen"
+                    } else {
+                        "windowed"
+                    }
+                ),
+                SettingsRow::Resolution => format!(
+                    "Resolution: {}x{}",
+                    settings.resolution.0, settings.resolution.1
+                ),
+                SettingsRow::Binding(action) if is_selected && settings_resource.capturing => {
+                    format!(
+                        "{:?}: press any key or button, {} to cancel...",
+                        action,
+                        input_map.describe(InputAction::Back)
+                    )
+                }
+                SettingsRow::Binding(action) => format!(
+                    "{:?}: {}",
+                    action,
+                    input_map
+                        .get(*action)
+                        .iter()
+                        .map(|binding| binding.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+
+            TextSection::new(
+                format!("{}\n", line),
+                TextStyle {
+                    font: menu_assets.font.clone(),
+                    font_size: SETTINGS_FONT_SIZE,
+                    color: if is_selected {
+                        SETTINGS_SELECTED_COLOR
+                    } else {
+                        SETTINGS_LINE_COLOR
+                    },
+                },
+            )
+        })
+        .collect();
+
+    sections.push(TextSection::new(
+        format!(
+            "\n{}/{} to change, {} to rebind, {} to save and go back",
+            input_map.describe(InputAction::Left),
+            input_map.describe(InputAction::Right),
+            input_map.describe(InputAction::Confirm),
+            input_map.describe(InputAction::Back)
+        ),
+        TextStyle {
+            font: menu_assets.font.clone(),
+            font_size: SETTINGS_FONT_SIZE,
+            color: SETTINGS_LINE_COLOR,
+        },
+    ));
+
+    for mut text in text_query.iter_mut() {
+        text.sections = sections.clone();
+    }
+}
+
+fn volume_slider(volume: f64) -> String {
+    let filled = (volume * VOLUME_SLIDER_STEPS as f64).round() as usize;
+    format!(
+        "[{}{}] {:.0}%",
+        "#".repeat(filled),
+        "-".repeat(VOLUME_SLIDER_STEPS - filled),
+        volume * 100.0
+    )
+}
+
+fn apply_volume(
+    settings: &save::Settings,
+    menu_music_channel: &AudioChannel<crate::MenuMusicAudioChannel>,
+    game_music_channel: &AudioChannel<crate::GameMusicAudioChannel>,
+    sfx_channel: &AudioChannel<crate::SoundEffectsAudioChannel>,
+) {
+    menu_music_channel.set_volume(settings.music_channel_volume());
+    game_music_channel.set_volume(settings.music_channel_volume());
+    sfx_channel.set_volume(settings.sfx_channel_volume());
+}
+
+fn apply_window(settings: &save::Settings, windows: &mut Windows) {
+    if let Some(window) = windows.get_primary_mut() {
+        if settings.fullscreen {
+            window.set_mode(WindowMode::BorderlessFullscreen);
+        } else {
+            window.set_mode(WindowMode::Windowed);
+            window.set_resolution(settings.resolution.0, settings.resolution.1);
+        }
+    }
+}
+
+// Apply the saved volume and window settings once the menu has loaded
+pub fn apply_settings_system(
+    save_data: Res<save::SaveData>,
+    mut windows: ResMut<Windows>,
+    menu_music_channel: Res<AudioChannel<crate::MenuMusicAudioChannel>>,
+    game_music_channel: Res<AudioChannel<crate::GameMusicAudioChannel>>,
+    sfx_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
+) {
+    apply_volume(
+        &save_data.settings,
+        &menu_music_channel,
+        &game_music_channel,
+        &sfx_channel,
+    );
+    apply_window(&save_data.settings, &mut windows);
+}
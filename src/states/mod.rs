@@ -1,14 +1,18 @@
 use std::time::Duration;
 
-use bevy::{app::AppExit, log::Level, prelude::*};
+use bevy::{app::AppExit, prelude::*};
 use bevy_kira_audio::prelude::*;
 use bevy_rapier2d::prelude::*;
 use rand::{thread_rng, Rng};
 
 use crate::{
-    assets::{self, GameOverAssets},
-    hair,
+    assets, hair,
+    input::{self, InputAction},
+    launch, save, score,
 };
+
+// fraction of the game music volume kept while the pause menu is open
+const PAUSED_MUSIC_VOLUME: f64 = 0.3;
 //use bevy_hanabi::prelude::*;
 
 use crate::{
@@ -26,9 +30,12 @@ pub enum AppStates {
     LoadingGame,
     Game,
     GameOver,
+    LevelComplete,
     Victory,
     Credits,
     Instructions,
+    Settings,
+    LevelSelect,
 }
 
 // used for tagging entities that are part of the game state
@@ -50,12 +57,13 @@ pub fn clear_state_system(
 
 // Start the game by entering the game loading state
 pub fn start_game_system(
-    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut action_input: input::ActionInputMut,
     mut app_state: ResMut<State<AppStates>>,
+    touches: Res<Touches>,
 ) {
-    // check for keyboard or gamepad input
-    let mut start_input = keyboard_input.just_released(KeyCode::Return)
-        || keyboard_input.just_released(KeyCode::Space);
+    // check for keyboard, gamepad or touch input
+    let start_input =
+        action_input.just_released(InputAction::Confirm) || touches.any_just_released();
 
     // if input read enter the game loading state
     if start_input {
@@ -66,62 +74,17 @@ pub fn start_game_system(
         //audio_channel.play(asset_server.load("sounds/menu_input_success.wav"));
 
         // reset input
-        keyboard_input.reset(KeyCode::Return);
-        keyboard_input.reset(KeyCode::Space);
-    }
-}
-
-pub fn start_gameover_system(
-    mut commands: Commands,
-    game_over: Res<assets::GameAssets>,
-    mut keyboard_input: ResMut<Input<KeyCode>>,
-    mut app_state: ResMut<State<AppStates>>,
-) {
-    // check for keyboard or gamepad input
-    let input = keyboard_input.just_released(KeyCode::L);
-
-    // if input read enter the game loading state
-    if input {
-        // set the state to loading game state
-        app_state.set(AppStates::GameOver).unwrap();
-
-        // play sound effect
-        //audio_channel.play(asset_server.load("sounds/menu_input_success.wav"));
-
-        // reset input
-        keyboard_input.reset(KeyCode::L);
-    }
-}
-
-pub fn start_victory_system(
-    mut commands: Commands,
-    sprite_assets: Res<assets::GameAssets>,
-    mut keyboard_input: ResMut<Input<KeyCode>>,
-    mut app_state: ResMut<State<AppStates>>,
-) {
-    // check for keyboard or gamepad input
-    let input = keyboard_input.just_released(KeyCode::W);
-
-    // if input read enter the game loading state
-    if input {
-        // set the state to loading game state
-        app_state.set(AppStates::Victory).unwrap();
-
-        // play sound effect
-        //audio_channel.play(asset_server.load("sounds/menu_input_success.wav"));
-
-        // reset input
-        keyboard_input.reset(KeyCode::W);
+        action_input.reset(InputAction::Confirm);
     }
 }
 
 /// Quit the game if quit input read
 pub fn quit_game_system(
-    keyboard_input: Res<Input<KeyCode>>,
+    action_input: input::ActionInput,
     mut app_exit_events: EventWriter<AppExit>,
 ) {
     // check for input
-    let quit_input = keyboard_input.just_released(KeyCode::Escape);
+    let quit_input = action_input.just_released(InputAction::Quit);
 
     // quit app if input read
     if quit_input {
@@ -130,13 +93,22 @@ pub fn quit_game_system(
 }
 
 // setup level of the game
+#[allow(clippy::too_many_arguments)]
 pub fn setup_game_system(
     mut commands: Commands,
     sprite_assets: Res<assets::GameAssets>,
     audio_channel: Res<AudioChannel<crate::GameMusicAudioChannel>>,
     game_assets: Res<assets::GameAssets>,
+    menu_assets: Res<assets::MenuAssets>,
     mut level_resource: ResMut<level::LevelResource>,
+    level_data_assets: Res<Assets<level::LevelData>>,
+    current_level: Res<level::CurrentLevel>,
+    save_data: Res<save::SaveData>,
 ) {
+    let level_data = level_data_assets
+        .get(&menu_assets.levels[current_level.0])
+        .unwrap();
+
     // Create a color gradient for the particles
 
     /*
@@ -150,11 +122,10 @@ pub fn setup_game_system(
     */
 
     // reset level resource
-    *level_resource = LevelResource {
-        louse_queue: crate::LOUSE_QUEUE.to_vec(),
-        lose_timer: Timer::from_seconds(crate::LOSE_TIME, TimerMode::Once),
-    };
+    *level_resource = LevelResource::from_data(level_data);
 
+    // restarting or quitting from the pause menu leaves the music ducked
+    audio_channel.set_volume(save_data.settings.music_channel_volume());
     audio_channel
         .play(game_assets.game_music.clone())
         .fade_in(AudioTween::new(
@@ -167,7 +138,7 @@ pub fn setup_game_system(
     commands
         .spawn(SpriteBundle {
             texture: sprite_assets.fire_line_image.clone(),
-            transform: Transform::from_translation(Vec3::new(crate::FIRE_LINE, 0.0, -1.0)),
+            transform: Transform::from_translation(Vec3::new(level_resource.fire_line, 0.0, -1.0)),
             ..Default::default()
         })
         .insert(AppStateComponent(AppStates::Game));
@@ -175,7 +146,7 @@ pub fn setup_game_system(
     // spawn the background
     commands
         .spawn(SpriteBundle {
-            texture: sprite_assets.backGround.clone(),
+            texture: sprite_assets.background.clone(),
             transform: Transform {
                 translation: Vec3::new(0.0, 0.0, -5.0),
                 scale: Vec3::new(1.0, 1.0, 1.0),
@@ -257,64 +228,61 @@ pub fn setup_game_system(
         .insert(AppStateComponent(AppStates::Game));
 
     // spawn hairs
-    hair::spawn_hair(&mut commands, &sprite_assets, Vec2::new(0.0, -130.0));
-    hair::spawn_hair(&mut commands, &sprite_assets, Vec2::new(350.0, -130.0));
-    hair::spawn_hair(&mut commands, &sprite_assets, Vec2::new(170.0, -130.0));
-    hair::spawn_hair(&mut commands, &sprite_assets, Vec2::new(-190.0, -130.0));
-
-    // spawn dandruff chunks
-    let num_chunks: i32 = 15;
-    let mut i = 0;
-    while i < num_chunks {
-        chunks::spawn_chunk(
+    for hair_data in level_data.hairs.iter() {
+        hair::spawn_hair(
             &mut commands,
-            sprite_assets.dandruff_big_images.clone(),
-            Vec2::new(
-                thread_rng().gen_range(-300.0..=400.0),
-                thread_rng().gen_range(-100.0..=300.0),
-            ),
-            Vec2::new(0.0, -10.0),
-            10.0,
+            &sprite_assets,
+            Vec2::new(hair_data.position.0, hair_data.position.1),
+            hair_data.segments,
+            hair_data.hair_type,
         );
-        i = i + 1;
     }
-}
-
-pub fn start_instructions_system(
-    mut keyboard_input: ResMut<Input<KeyCode>>,
-    mut app_state: ResMut<State<AppStates>>,
-) {
-    let input = keyboard_input.just_released(KeyCode::Return)
-        || keyboard_input.just_released(KeyCode::Space);
 
-    if input {
-        app_state.set(AppStates::Instructions).unwrap();
-        keyboard_input.reset(KeyCode::Return);
-        keyboard_input.reset(KeyCode::Space);
+    // spawn dandruff chunks
+    for chunk_data in level_data.chunks.iter() {
+        let mut i = 0;
+        while i < chunk_data.count {
+            chunks::spawn_chunk(
+                &mut commands,
+                sprite_assets.dandruff_big_images.clone(),
+                Vec2::new(
+                    thread_rng().gen_range(chunk_data.x_range.0..=chunk_data.x_range.1),
+                    thread_rng().gen_range(chunk_data.y_range.0..=chunk_data.y_range.1),
+                ),
+                Vec2::new(0.0, -10.0),
+                10.0,
+            );
+            i += 1;
+        }
     }
 }
 
-pub fn start_credits_system(
-    mut keyboard_input: ResMut<Input<KeyCode>>,
+pub fn start_main_menu_system(
+    mut action_input: input::ActionInputMut,
     mut app_state: ResMut<State<AppStates>>,
+    touches: Res<Touches>,
 ) {
-    let input = keyboard_input.just_released(KeyCode::C);
+    let input = action_input.just_released(InputAction::Back) || touches.any_just_released();
 
     if input {
-        app_state.set(AppStates::Credits).unwrap();
-        keyboard_input.reset(KeyCode::C);
+        app_state.set(AppStates::MainMenu).unwrap();
+        action_input.reset(InputAction::Back);
     }
 }
 
-pub fn start_main_menu_system(
-    mut keyboard_input: ResMut<Input<KeyCode>>,
+// Advance to the next level of the campaign
+pub fn start_next_level_system(
+    mut action_input: input::ActionInputMut,
     mut app_state: ResMut<State<AppStates>>,
+    mut current_level: ResMut<level::CurrentLevel>,
+    touches: Res<Touches>,
 ) {
-    let input = keyboard_input.just_released(KeyCode::M);
+    let input = action_input.just_released(InputAction::Confirm) || touches.any_just_released();
 
     if input {
-        app_state.set(AppStates::MainMenu).unwrap();
-        keyboard_input.reset(KeyCode::M);
+        current_level.0 += 1;
+        app_state.set(AppStates::LoadingGame).unwrap();
+        action_input.reset(InputAction::Confirm);
     }
 }
 
@@ -323,18 +291,7 @@ pub fn setup_main_menu_system(
     mut commands: Commands,
     menu_music_audio_channel: Res<AudioChannel<crate::MenuMusicAudioChannel>>,
     menu_assets: Res<assets::MenuAssets>,
-    mut keyboard_input: ResMut<Input<KeyCode>>,
 ) {
-    let mut test = -50.0;
-
-    let mut key_test: bool = keyboard_input.just_released(KeyCode::D);
-
-    if key_test {
-        test = 0.0;
-    } else {
-        test = -50.0;
-    }
-
     menu_music_audio_channel
         .play(menu_assets.menu_music.clone())
         .fade_in(AudioTween::new(
@@ -364,6 +321,118 @@ pub fn clean_up_game_system(audio_channel: Res<AudioChannel<crate::GameMusicAudi
     audio_channel.stop();
 }
 
+// Pause the game on top of the game state so it can be resumed
+pub fn start_pause_menu_system(
+    mut action_input: input::ActionInputMut,
+    mut app_state: ResMut<State<AppStates>>,
+) {
+    if action_input.just_released(InputAction::Pause) {
+        app_state.push(AppStates::PauseMenu).unwrap();
+        action_input.reset(InputAction::Pause);
+    }
+}
+
+// freeze the game while the pause menu is open
+pub fn pause_game_system(
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut launch_resource: ResMut<launch::LaunchResource>,
+    audio_channel: Res<AudioChannel<crate::GameMusicAudioChannel>>,
+    save_data: Res<save::SaveData>,
+) {
+    rapier_config.physics_pipeline_active = false;
+    launch_resource.cancel();
+    audio_channel.set_volume(save_data.settings.music_channel_volume() * PAUSED_MUSIC_VOLUME);
+}
+
+pub fn resume_game_system(
+    mut rapier_config: ResMut<RapierConfiguration>,
+    audio_channel: Res<AudioChannel<crate::GameMusicAudioChannel>>,
+    save_data: Res<save::SaveData>,
+) {
+    rapier_config.physics_pipeline_active = true;
+    audio_channel.set_volume(save_data.settings.music_channel_volume());
+}
+
+// setup the pause overlay on top of the frozen game
+pub fn setup_pause_menu_system(
+    mut commands: Commands,
+    menu_assets: Res<assets::MenuAssets>,
+    input_map: Res<input::InputMap>,
+) {
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.0, 0.0, 0.0, 0.6),
+                custom_size: Some(Vec2::new(2000.0, 2000.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 100.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::PauseMenu));
+
+    let text_style = TextStyle {
+        font: menu_assets.font.clone(),
+        font_size: 24.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "Paused",
+                TextStyle {
+                    font_size: 48.0,
+                    ..text_style.clone()
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(Vec3::new(0.0, 80.0, 101.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::PauseMenu));
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                format!(
+                    "press {} to resume\npress {} to restart the level\npress {} to quit to the menu",
+                    input_map.describe(InputAction::Confirm),
+                    input_map.describe(InputAction::Restart),
+                    input_map.describe(InputAction::Back)
+                ),
+                text_style,
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(Vec3::new(0.0, -20.0, 101.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::PauseMenu));
+}
+
+// Resume, restart or quit from the pause menu
+pub fn pause_menu_system(
+    mut action_input: input::ActionInputMut,
+    mut app_state: ResMut<State<AppStates>>,
+    touches: Res<Touches>,
+) {
+    if action_input.just_released(InputAction::Confirm)
+        || action_input.just_released(InputAction::Pause)
+        || touches.any_just_released()
+    {
+        app_state.pop().unwrap();
+        action_input.reset(InputAction::Confirm);
+        action_input.reset(InputAction::Pause);
+    } else if action_input.just_released(InputAction::Restart) {
+        // replacing the stack exits the game state, so it is set up again from scratch
+        app_state.replace(AppStates::LoadingGame).unwrap();
+        action_input.reset(InputAction::Restart);
+    } else if action_input.just_released(InputAction::Back) {
+        app_state.replace(AppStates::MainMenu).unwrap();
+        action_input.reset(InputAction::Back);
+    }
+}
+
 // setup level of the game
 pub fn setup_victory_menu_system(
     mut commands: Commands,
@@ -371,6 +440,9 @@ pub fn setup_victory_menu_system(
 
     menu_music_audio_channel: Res<AudioChannel<crate::MenuMusicAudioChannel>>,
     menu_assets: Res<assets::MenuAssets>,
+    level_data_assets: Res<Assets<level::LevelData>>,
+    current_level: Res<level::CurrentLevel>,
+    score: Res<score::ScoreResource>,
 ) {
     sound_effects_audio_channel.play(menu_assets.celebration.clone());
 
@@ -393,6 +465,79 @@ pub fn setup_victory_menu_system(
             ..Default::default()
         })
         .insert(AppStateComponent(AppStates::Victory));
+
+    let level_data = level_data_assets
+        .get(&menu_assets.levels[current_level.0])
+        .unwrap();
+
+    score::spawn_score_text(
+        &mut commands,
+        &menu_assets,
+        &score,
+        level_data,
+        Vec2::new(188.0, -125.0),
+        Color::BLACK,
+        AppStates::Victory,
+    );
+}
+
+// setup the screen shown between levels of the campaign
+pub fn setup_level_complete_system(
+    mut commands: Commands,
+    sound_effects_audio_channel: Res<AudioChannel<crate::SoundEffectsAudioChannel>>,
+    menu_assets: Res<assets::MenuAssets>,
+    level_data_assets: Res<Assets<level::LevelData>>,
+    current_level: Res<level::CurrentLevel>,
+    score: Res<score::ScoreResource>,
+) {
+    sound_effects_audio_channel.play(menu_assets.celebration.clone());
+
+    let text_style = TextStyle {
+        font: menu_assets.font.clone(),
+        font_size: 40.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                format!("Level {} cleared!", current_level.0 + 1),
+                text_style.clone(),
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(Vec3::new(0.0, 120.0, 0.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::LevelComplete));
+
+    let level_data = level_data_assets
+        .get(&menu_assets.levels[current_level.0])
+        .unwrap();
+
+    score::spawn_score_text(
+        &mut commands,
+        &menu_assets,
+        &score,
+        level_data,
+        Vec2::new(0.0, 40.0),
+        Color::WHITE,
+        AppStates::LevelComplete,
+    );
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "Press Enter for the next level",
+                TextStyle {
+                    font_size: 24.0,
+                    ..text_style
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(Vec3::new(0.0, -120.0, 0.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::LevelComplete));
 }
 
 pub fn clean_up_victory_menu_system(
@@ -406,6 +551,7 @@ pub fn setup_gameover_menu_system(
     mut commands: Commands,
     audio_channel: Res<AudioChannel<crate::MenuMusicAudioChannel>>,
     menu_assets: Res<assets::MenuAssets>,
+    current_level: Res<level::CurrentLevel>,
 ) {
     audio_channel
         .play(menu_assets.menu_music.clone())
@@ -426,6 +572,23 @@ pub fn setup_gameover_menu_system(
             ..Default::default()
         })
         .insert(AppStateComponent(AppStates::GameOver));
+
+    // entering the game again replays the current level
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                format!("(retries level {})", current_level.0 + 1),
+                TextStyle {
+                    font: menu_assets.font.clone(),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(Vec3::new(170.0, -110.0, 1.0)),
+            ..Default::default()
+        })
+        .insert(AppStateComponent(AppStates::GameOver));
 }
 
 pub fn clean_up_gameover_menu_system(
@@ -435,11 +598,7 @@ pub fn clean_up_gameover_menu_system(
 }
 
 // setup level of the game
-pub fn setup_instructions_system(
-    audio_channel: Res<AudioChannel<crate::MenuMusicAudioChannel>>,
-    menu_assets: Res<assets::MenuAssets>,
-    mut commands: Commands,
-) {
+pub fn setup_instructions_system(menu_assets: Res<assets::MenuAssets>, mut commands: Commands) {
     commands
         .spawn(SpriteBundle {
             texture: menu_assets.instructions_screen.clone(),
@@ -454,11 +613,7 @@ pub fn setup_instructions_system(
 }
 
 // setup level of the game
-pub fn setup_credits_system(
-    audio_channel: Res<AudioChannel<crate::MenuMusicAudioChannel>>,
-    menu_assets: Res<assets::MenuAssets>,
-    mut commands: Commands,
-) {
+pub fn setup_credits_system(menu_assets: Res<assets::MenuAssets>, mut commands: Commands) {
     commands
         .spawn(SpriteBundle {
             texture: menu_assets.credits_screen.clone(),
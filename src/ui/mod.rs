@@ -2,7 +2,7 @@ use bevy::prelude::*;
 
 use crate::{
     assets::GameAssets,
-    level::{self, LevelResource},
+    level::LevelResource,
     states::{AppStateComponent, AppStates},
 };
 
@@ -23,14 +23,12 @@ pub fn game_ui_system(
     louse_queue_copy.reverse();
 
     for (i, louse_type) in louse_queue_copy.iter().enumerate() {
+        let mut tint = louse_type.definition().tint;
+        tint.set_a(0.8);
+
         commands
             .spawn(ImageBundle {
-                image: match louse_type {
-                    crate::louse::LouseType::Basic => game_assets.basic_louse_image.clone().into(),
-                    crate::louse::LouseType::Exploding => {
-                        game_assets.exploding_louse_image.clone().into()
-                    }
-                },
+                image: (louse_type.definition().sprite)(&game_assets).into(),
                 style: Style {
                     size: Size::new(Val::Px(12.0), Val::Px(12.0)),
                     position: UiRect {
@@ -42,7 +40,7 @@ pub fn game_ui_system(
                     ..default()
                 },
                 transform: Transform::from_scale(Vec3::new(3.0, 3.0, 1.0)),
-                background_color: Color::rgba(1.0, 1.0, 1.0, 0.8).into(),
+                background_color: tint.into(),
                 ..Default::default()
             })
             .insert(AppStateComponent(AppStates::Game))